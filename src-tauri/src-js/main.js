@@ -0,0 +1,117 @@
+// JavaScript 节点在 node 里的运行器，请求 {code, inputs} 从 stdin 读入，envelope 写到 stdout
+// 和 src/js/invoke.js、src-tauri/src/value.rs 同一套标签
+const VALUE_TAGS = ['$int', '$float', '$bytes', '$error', '$map'];
+
+class NodeError {
+    constructor(message) {
+        this.message = message;
+    }
+    toString() {
+        return 'Error: ' + this.message;
+    }
+}
+
+function encodeValue(value) {
+    if (value === undefined || value === null) {
+        return null;
+    }
+    if (typeof value === 'bigint') {
+        return Number.isSafeInteger(Number(value)) ? Number(value) : { '$int': value.toString() };
+    }
+    if (typeof value === 'number' && !Number.isFinite(value)) {
+        return { '$float': Number.isNaN(value) ? 'NaN' : (value > 0 ? 'inf' : '-inf') };
+    }
+    if (value instanceof Uint8Array || value instanceof ArrayBuffer) {
+        return { '$bytes': Buffer.from(new Uint8Array(value)).toString('base64') };
+    }
+    if (value instanceof NodeError || value instanceof Error) {
+        return { '$error': value.message };
+    }
+    if (Array.isArray(value)) {
+        return value.map(encodeValue);
+    }
+    if (typeof value === 'object') {
+        const out = {};
+        for (const [key, item] of Object.entries(value)) {
+            out[key] = encodeValue(item);
+        }
+        const keys = Object.keys(out);
+        return keys.length === 1 && VALUE_TAGS.includes(keys[0]) ? { '$map': out } : out;
+    }
+    if (typeof value === 'function' || typeof value === 'symbol') {
+        return String(value);
+    }
+    return value;
+}
+
+function decodeValue(value) {
+    if (Array.isArray(value)) {
+        return value.map(decodeValue);
+    }
+    if (value === null || typeof value !== 'object') {
+        return value;
+    }
+    const keys = Object.keys(value);
+    if (keys.length === 1 && VALUE_TAGS.includes(keys[0])) {
+        const inner = value[keys[0]];
+        switch (keys[0]) {
+            case '$int':
+                return BigInt(inner);
+            case '$float':
+                return inner === 'NaN' ? NaN : (inner === 'inf' ? Infinity : (inner === '-inf' ? -Infinity : Number(inner)));
+            case '$bytes':
+                return new Uint8Array(Buffer.from(inner, 'base64'));
+            case '$error':
+                return new NodeError(inner);
+            case '$map':
+                value = inner;
+            break;
+        }
+    }
+    const out = {};
+    for (const [key, item] of Object.entries(value)) {
+        out[key] = decodeValue(item);
+    }
+    return out;
+}
+
+// 和 derivedNodes.js 一样：返回带 labelMarkedForOutputs 的对象时原样交给执行器
+function envelope(result) {
+    if (result && typeof result === 'object' && result.hasOwnProperty('labelMarkedForOutputs')) {
+        if (result.labelMarkedForOutputs === 'outputs') {
+            const { labelMarkedForOutputs, ...outputs } = result;
+            return { error: false, labelMarkedForOutputs, result: encodeValue(outputs) };
+        }
+        return { ...result, result: encodeValue(result.result) };
+    }
+    return { error: false, labelMarkedForOutputs: 'rawResult', result: encodeValue(result) };
+}
+
+async function main() {
+    const chunks = [];
+    for await (const chunk of process.stdin) {
+        chunks.push(chunk);
+    }
+    const request = JSON.parse(Buffer.concat(chunks).toString('utf8'));
+    // 用户代码里的 console.log 不能混进 stdout，先攒起来放进 envelope.stdout；alert 没有窗口可弹
+    const printed = [];
+    console.log = (...args) => printed.push(require('util').format(...args) + '\n');
+    globalThis.alert = (message) => printed.push(String(message) + '\n');
+    let reply;
+    try {
+        const userFunction = new Function('inputs', request.code);
+        reply = envelope(await userFunction(decodeValue(request.inputs)));
+    } catch (err) {
+        reply = {
+            error: true,
+            labelMarkedForOutputs: 'rawResult',
+            details: String(err),
+        };
+    }
+    if (printed.length > 0) {
+        reply.stdout = printed.join('');
+    }
+    process.stdout.write(JSON.stringify(reply));
+}
+
+main();
@@ -1,3 +1,58 @@
+import base64
+import math
+
+# 和 src-tauri/src/value.rs 同一套标签：JSON 表达不了的值包成单 key 对象
+_TAGS = ('$int', '$float', '$bytes', '$error', '$map')
+_MAX_SAFE_INTEGER = 2 ** 53 - 1
+
+
+class NodeError(Exception):
+    """An error value passed between nodes, `{"$error": message}` on the wire."""
+
+
+def _decode(value):
+    if isinstance(value, list):
+        return [_decode(item) for item in value]
+    if not isinstance(value, dict):
+        return value
+    if len(value) == 1:
+        tag, inner = next(iter(value.items()))
+        if tag == '$int':
+            return int(inner)
+        if tag == '$float':
+            return float(inner)
+        if tag == '$bytes':
+            return base64.b64decode(inner)
+        if tag == '$error':
+            return NodeError(inner)
+        if tag == '$map':
+            value = inner
+    return {k: _decode(v) for k, v in value.items()}
+
+
+def _encode(value):
+    if isinstance(value, bool) or value is None or isinstance(value, str):
+        return value
+    if isinstance(value, int):
+        return value if abs(value) <= _MAX_SAFE_INTEGER else {'$int': str(value)}
+    if isinstance(value, float):
+        if math.isnan(value):
+            return {'$float': 'NaN'}
+        if math.isinf(value):
+            return {'$float': 'inf' if value > 0 else '-inf'}
+        return value
+    if isinstance(value, (bytes, bytearray)):
+        return {'$bytes': base64.b64encode(value).decode('ascii')}
+    if isinstance(value, Exception):
+        return {'$error': str(value)}
+    if isinstance(value, (list, tuple)):
+        return [_encode(item) for item in value]
+    if isinstance(value, dict):
+        out = {str(k): _encode(v) for k, v in value.items()}
+        return {'$map': out} if len(out) == 1 and next(iter(out)) in _TAGS else out
+    return str(value)
+
+
 def python_exec(code, inputs=None):
     try:
         # print(inputs)
@@ -5,13 +60,13 @@ def python_exec(code, inputs=None):
         wrapped = f"""def f(inputs):\n{indented_code}\nresult = f(_inputs)"""
         # print(wrapped)
 
-        locals_dict = {'_inputs': inputs}
+        locals_dict = {'_inputs': _decode(inputs), 'NodeError': NodeError}
         exec(wrapped, locals_dict)
-    
+
         return {
             "error": False,
             "labelMarkedForOutputs": 'rawResult',
-            "result": locals_dict.get('result'),
+            "result": _encode(locals_dict.get('result')),
         }
     except Exception as e:
         # print(e)
@@ -26,4 +81,4 @@ def python_exec(code, inputs=None):
 # y = x + 10
 # return y
 # """
-# print(python_exec(code, 1))
\ No newline at end of file
+# print(python_exec(code, 1))
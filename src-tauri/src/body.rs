@@ -0,0 +1,79 @@
+// 循环体：Map / While / Repeat 共用
+// 节点里的子图（bodyType = "subgraph"），或者节点自己换成 bodyType 的 codeType 跑 fn
+use crate::executor::{self, Runtime};
+use crate::graph::{Graph, Node};
+use crate::value::Value;
+use crate::{retry, subgraph};
+use std::collections::BTreeMap;
+
+pub enum Body {
+    Node(Node),
+    Graph(Graph),
+}
+
+/// Whether a node of `codeType` can run as a loop body by itself.
+pub fn is_body_type(kind: &str) -> bool {
+    matches!(
+        kind,
+        "javascript" | "python" | "sh" | "rust" | "rustlite" | "wasm"
+    )
+}
+
+// 一个普通节点当循环体：节点自己换个 codeType；超时和重试只管整个节点，不带进循环体
+pub fn parse(node: &Node) -> Result<Body, String> {
+    match node.body_type() {
+        None => Err(format!(
+            "node `{}` has no properties.bodyType",
+            node.title()
+        )),
+        Some("subgraph") => match node.subgraph() {
+            Some(graph) => graph.map(Body::Graph),
+            None => Err(format!("node `{}` has no subgraph", node.title())),
+        },
+        Some(kind) if !is_body_type(kind) => {
+            Err(format!("codeType `{}` cannot be a loop body", kind))
+        }
+        Some(kind) => {
+            let mut body = node.clone();
+            body.properties
+                .insert("codeType".to_string(), serde_json::Value::from(kind));
+            body.properties.remove("timeout");
+            body.properties.remove("retry");
+            Ok(Body::Node(body))
+        }
+    }
+}
+
+/// Runs `body` once. A node body gets `value` as `input_0` and `index` as
+/// `input_1` and returns its result; a subgraph body gets them through the
+/// `graph/input` nodes named `ports.0` and `index`, and returns what the
+/// `graph/output` named `ports.1` received.
+pub async fn run(
+    body: &Body,
+    value: Value,
+    index: usize,
+    ports: (&str, &str),
+    runtime: &Runtime,
+) -> Result<Value, String> {
+    let index = Value::Int(index as i64);
+    match body {
+        Body::Node(node) => {
+            let inputs = Value::Map(BTreeMap::from([
+                ("input_0".to_string(), value),
+                ("input_1".to_string(), index),
+            ]));
+            let (envelope, _) = executor::execute(node, &inputs, runtime).await;
+            // sh 退出码非 0 也算失败
+            if let Some(failure) = retry::failure(node, &envelope) {
+                return Err(failure.message);
+            }
+            Ok(envelope.result.unwrap_or_default())
+        }
+        Body::Graph(graph) => {
+            let bindings =
+                BTreeMap::from([(ports.0.to_string(), value), ("index".to_string(), index)]);
+            let mut results = subgraph::run_bound(graph, &bindings, runtime, "body").await?;
+            Ok(results.remove(ports.1).unwrap_or_default())
+        }
+    }
+}
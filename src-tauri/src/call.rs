@@ -0,0 +1,126 @@
+// 调用另一个工作流文件：workflow/Call 的 properties.path 指向存好的图（.json / .yaml / .yml），
+// 被调用的图顶层的 graph/input 是参数、graph/output 是结果，按名字对应这个节点的输入 / 输出口
+// 相对路径按调用方文件所在的目录算；调用链上已经有的文件再被调用就是递归，直接报错
+use crate::executor::Runtime;
+use crate::graph::{Graph, Node};
+use crate::subgraph;
+use crate::value::{Envelope, Value};
+use serde::Serialize;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+/// What a workflow file declares: the names of its top-level `graph/input`
+/// (parameters) and `graph/output` (results) nodes.
+#[derive(Serialize)]
+pub struct Signature {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+pub fn signature(graph: &Graph) -> Signature {
+    let names = |kind: &str| {
+        graph
+            .nodes
+            .iter()
+            .filter(|node| node.code_type() == Some(kind))
+            .filter_map(Node::port_name)
+            .map(str::to_string)
+            .collect()
+    };
+    Signature {
+        inputs: names("input"),
+        outputs: names("output"),
+    }
+}
+
+/// Resolves `path` against the directory of `caller`, the workflow file doing
+/// the call. The result is canonical so the same file always compares equal.
+pub fn resolve(path: &str, caller: Option<&Path>) -> Result<PathBuf, String> {
+    let path = Path::new(path);
+    let full = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        match caller.and_then(Path::parent) {
+            Some(dir) => dir.join(path),
+            None => {
+                return Err(format!(
+                    "cannot resolve `{}`: save the calling workflow first, or use an absolute path",
+                    path.display()
+                ))
+            }
+        }
+    };
+    full.canonicalize()
+        .map_err(|e| format!("cannot open workflow `{}`: {}", full.display(), e))
+}
+
+pub fn load(path: &Path) -> Result<Graph, String> {
+    Graph::read(path)
+}
+
+/// Runs the workflow file of a `workflow/Call` node.
+pub fn exec<'a>(
+    node: &'a Node,
+    inputs: &'a Value,
+    runtime: &'a Runtime,
+) -> Pin<Box<dyn Future<Output = Envelope> + Send + 'a>> {
+    // 和子图一样，被调用的图里还可能有 Call 节点，装箱断开类型环
+    Box::pin(run(node, inputs, runtime))
+}
+
+async fn run(node: &Node, inputs: &Value, runtime: &Runtime) -> Envelope {
+    let path = match node.workflow_path() {
+        Some(path) => path,
+        None => return Envelope::err("workflow/Call node has no properties.path"),
+    };
+    let path = match resolve(path, runtime.calls.last().map(PathBuf::as_path)) {
+        Ok(path) => path,
+        Err(e) => return Envelope::err(e),
+    };
+    if runtime.calls.contains(&path) {
+        let chain: Vec<String> = runtime
+            .calls
+            .iter()
+            .chain([&path])
+            .map(|call| call.display().to_string())
+            .collect();
+        return Envelope::err(format!("recursive workflow call: {}", chain.join(" -> ")));
+    }
+    let graph = match load(&path) {
+        Ok(graph) => graph,
+        Err(e) => return Envelope::err(format!("workflow `{}`: {}", path.display(), e)),
+    };
+    // 节点上的口必须是被调用的图声明过的，没连的参数是 null
+    let declared = signature(&graph);
+    if let Some(input) = node
+        .inputs()
+        .iter()
+        .find(|input| !declared.inputs.contains(&input.name))
+    {
+        return Envelope::err(format!(
+            "workflow `{}` has no parameter `{}`",
+            path.display(),
+            input.name
+        ));
+    }
+    if let Some(output) = node
+        .outputs()
+        .iter()
+        .find(|output| !declared.outputs.contains(&output.name))
+    {
+        return Envelope::err(format!(
+            "workflow `{}` has no result `{}`",
+            path.display(),
+            output.name
+        ));
+    }
+    let what = format!("workflow `{}`", path.display());
+    let mut calls = runtime.calls.clone();
+    calls.push(path);
+    let inner = Runtime {
+        calls,
+        ..runtime.clone()
+    };
+    subgraph::run_graph(&graph, node, inputs, &inner, &what).await
+}
@@ -0,0 +1,325 @@
+// 命令行模式：hp run flow.json --input key=value，hp validate flow.json，hp convert a.json b.yaml，
+// hp compile flow.json -o flow.sh
+// 不开窗口，直接用后端执行器跑保存好的图，结果以 JSON 打到 stdout
+// 工作流文件按扩展名区分：.yaml / .yml 是文本格式，其余当 LiteGraph JSON
+use crate::executor::{self, Cancel, Report, Runtime};
+use crate::graph::Graph;
+use crate::history::{self, History};
+use crate::migrate;
+use crate::rust_cache::RustCache;
+use crate::rust_node::SettingsStore;
+use crate::script_export::{self, Target};
+use crate::text_format;
+use crate::validate;
+use crate::value::Value;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// 和 tauri.conf.json 的 identifier 一致，命令行和桌面端共用缓存和设置
+const IDENTIFIER: &str = "com.ax.hp";
+
+const USAGE: &str = "usage: hp run <flow.json> [--input key=value]...
+       hp validate <flow.json>
+       hp convert <input> <output>
+       hp compile <flow.json> [--target bash|python] [-o <script>]";
+
+#[derive(Serialize)]
+struct NodeOutputs {
+    id: u64,
+    title: String,
+    outputs: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct Printed {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    nodes: Vec<NodeOutputs>,
+}
+
+impl From<Report> for Printed {
+    fn from(report: Report) -> Self {
+        Printed {
+            ok: report.ok,
+            error: report.error,
+            nodes: report
+                .nodes
+                .into_iter()
+                .map(|run| NodeOutputs {
+                    id: run.id,
+                    title: run.title,
+                    outputs: run.outputs,
+                })
+                .collect(),
+        }
+    }
+}
+
+struct RunArgs {
+    path: PathBuf,
+    bindings: BTreeMap<String, Value>,
+}
+
+// 值能按 JSON 解析就按 JSON，否则当字符串
+fn parse_binding(raw: &str) -> Result<(String, Value), String> {
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("--input expects key=value, got `{}`", raw))?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+fn parse_run(args: &[String]) -> Result<RunArgs, String> {
+    let mut path = None;
+    let mut bindings = BTreeMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--input" || arg == "-i" {
+            let raw = args.next().ok_or("--input needs a value")?;
+            let (key, value) = parse_binding(raw)?;
+            bindings.insert(key, value);
+        } else if let Some(raw) = arg.strip_prefix("--input=") {
+            let (key, value) = parse_binding(raw)?;
+            bindings.insert(key, value);
+        } else if arg.starts_with('-') {
+            return Err(format!("unknown option `{}`", arg));
+        } else if path.replace(PathBuf::from(arg)).is_some() {
+            return Err(format!("unexpected argument `{}`", arg));
+        }
+    }
+    Ok(RunArgs {
+        path: path.ok_or("missing workflow file")?,
+        bindings,
+    })
+}
+
+// 每个 --input 都必须对得上某个没连线的输入口，或者同名的 graph/input 节点
+fn check_bindings(graph: &Graph, bindings: &BTreeMap<String, Value>) -> Result<(), String> {
+    for key in bindings.keys() {
+        let bound = graph
+            .nodes
+            .iter()
+            .flat_map(|node| node.inputs())
+            .any(|input| input.link.is_none() && &input.name == key)
+            || graph.nodes.iter().any(|node| {
+                node.code_type() == Some("input") && node.port_name() == Some(key.as_str())
+            });
+        if !bound {
+            return Err(format!("no unconnected input named `{}`", key));
+        }
+    }
+    Ok(())
+}
+
+fn data_dir() -> Result<PathBuf, String> {
+    Ok(dirs::data_dir()
+        .ok_or("cannot locate the data directory")?
+        .join(IDENTIFIER))
+}
+
+fn runtime(data_dir: &Path, flow: &Path) -> Result<Runtime, String> {
+    // workflow/Call 的相对路径按这个文件所在的目录算
+    let flow = flow
+        .canonicalize()
+        .map_err(|e| format!("{}: {}", flow.display(), e))?;
+    Ok(Runtime {
+        cache: Arc::new(RustCache::new(data_dir.join("rust-cache"))),
+        settings: SettingsStore::load(data_dir.join("rust-node.json")).get(),
+        events: None,
+        debugger: None,
+        step: false,
+        cancel: Some(Arc::new(Cancel::default())),
+        calls: vec![flow],
+    })
+}
+
+fn run(args: &[String]) -> Result<Report, String> {
+    let args = parse_run(args)?;
+    let graph = Graph::read(&args.path)?;
+    check_bindings(&graph, &args.bindings)?;
+    let data_dir = data_dir()?;
+    let runtime = runtime(&data_dir, &args.path)?;
+    let started_at = history::now_ms();
+    let report = tokio::runtime::Runtime::new()
+        .map_err(|e| e.to_string())?
+        .block_on(async {
+            // Ctrl-C 取消这次运行：子进程被杀掉，报告照常打印
+            if let Some(cancel) = runtime.cancel.clone() {
+                tokio::spawn(async move {
+                    if tokio::signal::ctrl_c().await.is_ok() {
+                        cancel.cancel();
+                    }
+                });
+            }
+            executor::run(&graph, &runtime, &args.bindings).await
+        })?;
+    // 命令行的运行也进运行记录，和桌面端共用一个库
+    let recorded = History::open(&data_dir.join("history.sqlite"))
+        .and_then(|store| store.record(&graph, started_at, history::now_ms(), &report));
+    if let Err(e) = recorded {
+        eprintln!("warning: failed to record run: {}", e);
+    }
+    Ok(report)
+}
+
+// 有 error 级别的问题时退出码为 1，只有 warning 为 0
+fn validate_file(args: &[String]) -> Result<i32, String> {
+    let path = match args {
+        [path] => path,
+        _ => return Err("expected exactly one workflow file".to_string()),
+    };
+    let raw = migrate::upgrade(text_format::read(Path::new(path))?)?;
+    let findings = tokio::runtime::Runtime::new()
+        .map_err(|e| e.to_string())?
+        .block_on(validate::validate(raw));
+    println!("{}", serde_json::to_string_pretty(&findings).unwrap());
+    Ok(findings.iter().any(|finding| finding.severity == "error") as i32)
+}
+
+// 格式由两边的扩展名决定，顺带迁移到当前版本
+fn convert(args: &[String]) -> Result<(), String> {
+    let (input, output) = match args {
+        [input, output] => (Path::new(input), Path::new(output)),
+        _ => return Err("expected an input and an output file".to_string()),
+    };
+    let graph = Graph::read(input)?;
+    text_format::write(output, &graph)
+}
+
+// 目标语言：--target 优先，其次看 -o 的扩展名，默认 bash
+fn compile(args: &[String]) -> Result<i32, String> {
+    let mut flow = None;
+    let mut target = None;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--target" | "-t" => {
+                let name = args.next().ok_or("--target needs a value")?;
+                target = Some(Target::parse(name)?);
+            }
+            "--output" | "-o" => {
+                output = Some(PathBuf::from(args.next().ok_or("-o needs a file")?))
+            }
+            _ if arg.starts_with("--target=") => {
+                target = Some(Target::parse(&arg["--target=".len()..])?)
+            }
+            // 打错的选项不能当成工作流文件
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ if flow.is_none() => flow = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+    let flow = flow.ok_or("expected a workflow file")?;
+    let target = target.unwrap_or_else(|| {
+        match output
+            .as_ref()
+            .and_then(|path| path.extension())
+            .and_then(|ext| ext.to_str())
+        {
+            Some("py") => Target::Python,
+            _ => Target::Bash,
+        }
+    });
+    let graph = Graph::read(&flow)?;
+    let compiled = script_export::compile(&graph, target)?;
+    match &output {
+        Some(path) => {
+            std::fs::write(path, &compiled.script)
+                .map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755));
+            }
+        }
+        None => print!("{}", compiled.script),
+    }
+    for node in &compiled.unsupported {
+        eprintln!(
+            "unsupported node {} ({}): {}",
+            node.node_id, node.title, node.reason
+        );
+    }
+    Ok(!compiled.unsupported.is_empty() as i32)
+}
+
+// release 版在 Windows 上是 windows_subsystem = "windows"，没有控制台，
+// println! 什么也打不出来；从终端启动时挂到父进程（cmd / PowerShell）的控制台上
+#[cfg(windows)]
+fn attach_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    // 失败（比如双击启动、没有父控制台）就算了，输出重定向到文件时本来就有句柄
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+/// Handles `hp <subcommand> ...` and returns the process exit code. Returns
+/// `None` only when the desktop app should start: no arguments, or just the
+/// ones the OS passes when launching the app.
+pub fn main(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    // macOS 从 Finder 启动时会带上 -psn_<进程号>
+    if rest.is_empty() && command.starts_with("-psn_") {
+        return None;
+    }
+    attach_console();
+    match command.as_str() {
+        "run" => Some(match run(rest) {
+            Ok(report) => {
+                let ok = report.ok;
+                if let Some(error) = &report.error {
+                    eprintln!("{}", error);
+                }
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&Printed::from(report)).unwrap()
+                );
+                if ok {
+                    0
+                } else {
+                    1
+                }
+            }
+            Err(e) => {
+                eprintln!("hp run: {}\n{}", e, USAGE);
+                2
+            }
+        }),
+        "validate" => Some(validate_file(rest).unwrap_or_else(|e| {
+            eprintln!("hp validate: {}\n{}", e, USAGE);
+            2
+        })),
+        "convert" => Some(match convert(rest) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("hp convert: {}\n{}", e, USAGE);
+                2
+            }
+        }),
+        "compile" => Some(compile(rest).unwrap_or_else(|e| {
+            eprintln!("hp compile: {}\n{}", e, USAGE);
+            2
+        })),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Some(0)
+        }
+        // 打错的子命令不能悄悄打开界面
+        _ => {
+            eprintln!("hp: unknown command `{}`\n{}", command, USAGE);
+            Some(2)
+        }
+    }
+}
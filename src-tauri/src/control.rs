@@ -0,0 +1,90 @@
+// 控制流节点：If 按条件、Switch 按取值只激活一个输出口，没走的输出口是 inactive，
+// 下游连着线的输入全是 inactive 的节点由执行器跳过
+// properties.fn 可选，是一段 rustlite 表达式，inputs 的绑定和 rustlite 节点一样；
+// 留空时 If 看 input_0 真不真，Switch 直接拿 input_0 去比
+use crate::graph::Node;
+use crate::rust_interp;
+use crate::value::{Envelope, Value};
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+pub fn is_control(node: &Node) -> bool {
+    matches!(node.code_type(), Some("if" | "switch"))
+}
+
+// 和 Python 的真值一样：null、false、0、空串、空列表、空 map 为假
+pub fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Error(_) => false,
+        Value::Bool(b) => *b,
+        Value::Int(n) => *n != 0,
+        Value::Float(f) => *f != 0.0,
+        Value::String(s) => !s.is_empty(),
+        Value::Bytes(bytes) => !bytes.is_empty(),
+        Value::List(items) => !items.is_empty(),
+        Value::Map(map) => !map.is_empty(),
+    }
+}
+
+// Switch 拿 key 和输出口的名字比：字符串直接比，其他值用 JSON 文本，比如 1、true
+fn key_text(key: &Value) -> String {
+    match key {
+        Value::String(s) => s.clone(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    }
+}
+
+// If：0 是 then，1 是 else；Switch：同名的输出口，没有就是 default
+fn taken(node: &Node, key: &Value) -> Option<usize> {
+    let outputs = node.outputs();
+    let slot = if node.code_type() == Some("if") {
+        Some(if truthy(key) { 0 } else { 1 })
+    } else {
+        let key = key_text(key);
+        outputs
+            .iter()
+            .position(|output| output.name == key)
+            .or_else(|| outputs.iter().position(|output| output.name == "default"))
+    };
+    slot.filter(|&slot| slot < outputs.len())
+}
+
+/// Runs an If / Switch node: `input_0` goes out of the taken slot only, as
+/// `{output_N: value}`. No slot is taken when a Switch key matches nothing and
+/// there is no `default` output.
+pub fn exec(node: &Node, inputs: &Value, interrupt: Arc<AtomicBool>) -> Envelope {
+    let value = match inputs {
+        Value::Map(map) => map.get("input_0").cloned().unwrap_or_default(),
+        _ => Value::Null,
+    };
+    let key = if node.code().trim().is_empty() {
+        value.clone()
+    } else {
+        let envelope = rust_interp::exec_interruptible(node.code(), inputs, interrupt);
+        if envelope.error {
+            return envelope;
+        }
+        envelope.result.unwrap_or_default()
+    };
+    let mut outputs = BTreeMap::new();
+    if let Some(slot) = taken(node, &key) {
+        outputs.insert(format!("output_{}", slot), value);
+    }
+    Envelope {
+        label_marked_for_outputs: "outputs".to_string(),
+        ..Envelope::ok(Value::Map(outputs))
+    }
+}
+
+/// The output slots an `outputs` envelope leaves out: untaken branches here,
+/// and subgraph outputs that were never reached.
+pub fn inactive(node: &Node, envelope: &Envelope) -> Vec<usize> {
+    let outputs = match &envelope.result {
+        Some(Value::Map(outputs)) => outputs,
+        _ => return (0..node.outputs().len()).collect(),
+    };
+    (0..node.outputs().len())
+        .filter(|slot| !outputs.contains_key(&format!("output_{}", slot)))
+        .collect()
+}
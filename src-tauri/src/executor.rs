@@ -0,0 +1,1092 @@
+// 在后端执行整张图，不依赖 webview 里的 graph.runStep()
+// 每个节点等结果回来之后再把输出喂给下游的 input_N，互不依赖的分支并行
+use crate::graph::{Graph, Node, MODE_NEVER};
+use crate::rust_cache::RustCache;
+use crate::rust_node::Settings;
+use crate::value::{Envelope, Value};
+use crate::{
+    call, control, loops, map, retry, rust_interp, rust_node, script_node, shell_node, subgraph,
+    wasm_node,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinSet;
+
+/// What node runtimes need from the host.
+#[derive(Clone)]
+pub struct Runtime {
+    pub cache: Arc<RustCache>,
+    pub settings: Settings,
+    /// Receives execution events as they happen; the desktop app forwards them to the webview.
+    pub events: Option<Sink>,
+    /// Pauses at breakpoints when set; without it breakpoints are ignored.
+    pub debugger: Option<Arc<Debugger>>,
+    /// Pause before the first node, as if stepping from the start.
+    pub step: bool,
+    /// Stops the whole run when triggered.
+    pub cancel: Option<Arc<Cancel>>,
+    /// Workflow files being run, outermost first: `workflow/Call` paths are
+    /// relative to the last one, and calling a file already here is recursion.
+    pub calls: Vec<PathBuf>,
+}
+
+pub type Sink = Arc<dyn Fn(Event) + Send + Sync>;
+
+// 执行过程中的事件，桌面端按 name() 作为 Tauri 事件名发给前端
+#[derive(Clone, Serialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum Event {
+    RunStarted {
+        run_id: u64,
+        /// Nodes that will run, MODE_NEVER ones excluded.
+        nodes: usize,
+    },
+    /// Waiting for a debugger command before running `id` with `inputs`.
+    Paused {
+        run_id: u64,
+        id: u64,
+        title: String,
+        inputs: Value,
+    },
+    NodeStarted {
+        run_id: u64,
+        id: u64,
+        title: String,
+        /// 1 for the first attempt, higher when `properties.retry` runs it again.
+        attempt: u32,
+    },
+    /// Attempt `attempt` failed; the next one starts after `delay_ms`.
+    NodeRetrying {
+        run_id: u64,
+        id: u64,
+        title: String,
+        attempt: u32,
+        duration_ms: u64,
+        status: Status,
+        error: String,
+        delay_ms: u64,
+    },
+    NodeFinished {
+        run_id: u64,
+        id: u64,
+        title: String,
+        duration_ms: u64,
+        /// A short preview of each output slot.
+        outputs: Vec<String>,
+        /// Output slots left inactive, i.e. the branches an If / Switch did not take.
+        inactive: Vec<usize>,
+    },
+    /// Every connected input was inactive, so the node did not run.
+    NodeSkipped { run_id: u64, id: u64, title: String },
+    NodeFailed {
+        run_id: u64,
+        id: u64,
+        title: String,
+        duration_ms: u64,
+        status: Status,
+        error: String,
+    },
+    RunFinished {
+        run_id: u64,
+        ok: bool,
+        duration_ms: u64,
+        error: Option<String>,
+    },
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::RunStarted { .. } => "run-started",
+            Event::Paused { .. } => "paused",
+            Event::NodeStarted { .. } => "node-started",
+            Event::NodeRetrying { .. } => "node-retrying",
+            Event::NodeFinished { .. } => "node-finished",
+            Event::NodeFailed { .. } => "node-failed",
+            Event::NodeSkipped { .. } => "node-skipped",
+            Event::RunFinished { .. } => "run-finished",
+        }
+    }
+}
+
+impl Runtime {
+    fn emit(&self, event: Event) {
+        if let Some(events) = &self.events {
+            events(event);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Command {
+    /// Run the pending node, then pause before the next one.
+    Step,
+    /// Run until the next breakpoint.
+    Continue,
+    /// Abort the run; nothing after the pending node runs.
+    Stop,
+}
+
+// 断点处暂停时从这里等前端发来的 step / continue / stop
+pub struct Debugger {
+    sender: mpsc::UnboundedSender<Command>,
+    receiver: tokio::sync::Mutex<mpsc::UnboundedReceiver<Command>>,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            receiver: tokio::sync::Mutex::new(receiver),
+        }
+    }
+}
+
+impl Debugger {
+    pub fn send(&self, command: Command) {
+        let _ = self.sender.send(command);
+    }
+
+    // 暂停之前先丢掉没暂停时点的按钮，免得一到断点就被旧命令放过去
+    async fn pause(&self, runtime: &Runtime, event: Event) -> Command {
+        let mut receiver = self.receiver.lock().await;
+        while receiver.try_recv().is_ok() {}
+        runtime.emit(event);
+        receiver.recv().await.unwrap_or(Command::Continue)
+    }
+}
+
+/// Cancels a run from outside, e.g. the debug toolbar's Stop. Each run gets its
+/// own, see [`Runs`].
+pub struct Cancel {
+    requested: watch::Sender<bool>,
+}
+
+impl Default for Cancel {
+    fn default() -> Self {
+        Self {
+            requested: watch::channel(false).0,
+        }
+    }
+}
+
+impl Cancel {
+    pub fn cancel(&self) {
+        self.requested.send_replace(true);
+    }
+
+    async fn cancelled(&self) {
+        let _ = self
+            .requested
+            .subscribe()
+            .wait_for(|requested| *requested)
+            .await;
+    }
+}
+
+/// The `Cancel` and `Debugger` of every run in progress, by run id, so Stop and
+/// breakpoint commands only reach the run they were sent to.
+#[derive(Default)]
+pub struct Runs {
+    runs: std::sync::Mutex<HashMap<u64, RunControls>>,
+}
+
+type RunControls = (Arc<Cancel>, Arc<Debugger>);
+
+impl Runs {
+    /// Registers a new run; pass the id to [`run_as`] and [`Runs::finish`] it afterwards.
+    pub fn start(&self) -> (u64, Arc<Cancel>, Arc<Debugger>) {
+        let run_id = next_run_id();
+        let cancel = Arc::new(Cancel::default());
+        let debugger = Arc::new(Debugger::default());
+        self.runs
+            .lock()
+            .unwrap()
+            .insert(run_id, (Arc::clone(&cancel), Arc::clone(&debugger)));
+        (run_id, cancel, debugger)
+    }
+
+    pub fn finish(&self, run_id: u64) {
+        self.runs.lock().unwrap().remove(&run_id);
+    }
+
+    // 已经结束的运行找不到，什么也不做
+    pub fn cancel(&self, run_id: u64) {
+        if let Some((cancel, _)) = self.runs.lock().unwrap().get(&run_id) {
+            cancel.cancel();
+        }
+    }
+
+    pub fn debug(&self, run_id: u64, command: Command) {
+        if let Some((_, debugger)) = self.runs.lock().unwrap().get(&run_id) {
+            debugger.send(command);
+        }
+    }
+}
+
+async fn cancelled(runtime: &Runtime) {
+    match &runtime.cancel {
+        Some(cancel) => cancel.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+// 节点任务结束时（包括超时和被 abort）置位，让后台线程里的 rustlite / wasm 停下
+struct InterruptOnDrop(Arc<AtomicBool>);
+
+impl Drop for InterruptOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+// 每次 run 一个编号，前端靠它丢掉上一次运行迟到的事件，Stop / 断点命令也按它找运行
+static RUN_ID: AtomicU64 = AtomicU64::new(1);
+
+fn next_run_id() -> u64 {
+    RUN_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+const PREVIEW_LEN: usize = 80;
+
+fn preview(value: &Value) -> String {
+    let text = serde_json::to_string(value).unwrap_or_default();
+    match text.char_indices().nth(PREVIEW_LEN) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Status {
+    Ok,
+    Failed,
+    /// Ran past `properties.timeout` and was stopped.
+    TimedOut,
+    /// Stopped because the run was cancelled or another node failed.
+    Cancelled,
+    /// Not run because every connected input was inactive.
+    Skipped,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attempt {
+    /// Starts at 1.
+    pub attempt: u32,
+    pub status: Status,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeRun {
+    pub id: u64,
+    pub title: String,
+    pub inputs: Value,
+    pub envelope: Envelope,
+    /// One value per output slot.
+    pub outputs: Vec<Value>,
+    pub duration_ms: u64,
+    /// Position in the order nodes finished, starting at 0.
+    pub seq: u64,
+    pub status: Status,
+    /// Every attempt when the node was retried; empty when it ran once.
+    pub attempts: Vec<Attempt>,
+    /// Output slots that carry nothing: untaken If / Switch branches, or every
+    /// slot of a skipped node.
+    pub inactive: Vec<usize>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub ok: bool,
+    /// Runs in topological order; execution stops at the first failing node.
+    pub nodes: Vec<NodeRun>,
+    pub error: Option<String>,
+}
+
+/// Every `properties.codeType` the executor can run.
+pub const CODE_TYPES: &[&str] = &[
+    "javascript",
+    "python",
+    "sh",
+    "one2one",
+    "rust",
+    "rustlite",
+    "wasm",
+    "if",
+    "switch",
+    "subgraph",
+    "call",
+    "map",
+    "while",
+    "repeat",
+    "input",
+    "output",
+];
+
+async fn dispatch(
+    node: &Node,
+    inputs: &Value,
+    runtime: &Runtime,
+    interrupt: Arc<AtomicBool>,
+) -> Envelope {
+    let code = node.code().to_string();
+    match node.code_type() {
+        Some("javascript") => script_node::javascript(&code, inputs).await,
+        Some("python") => script_node::python(&code, inputs).await,
+        Some("sh") => shell_node::exec(&code, inputs).await,
+        Some("one2one") => shell_node::one_to_one(node, inputs).await,
+        Some("rust") => {
+            let deps: Vec<String> = node
+                .properties
+                .get("deps")
+                .and_then(|deps| serde_json::from_value(deps.clone()).ok())
+                .unwrap_or_default();
+            rust_node::exec(&code, &deps, inputs, &runtime.cache, &runtime.settings).await
+        }
+        Some("rustlite") => {
+            let inputs = inputs.clone();
+            tokio::task::spawn_blocking(move || {
+                rust_interp::exec_interruptible(&code, &inputs, interrupt)
+            })
+            .await
+            .unwrap_or_else(|e| Envelope::err(e.to_string()))
+        }
+        Some("wasm") => {
+            let limits = wasm_node::Limits::from_properties(&serde_json::Value::Object(
+                node.properties.clone(),
+            ));
+            let inputs = inputs.clone();
+            tokio::task::spawn_blocking(move || {
+                wasm_node::exec_interruptible(&code, &inputs, limits, interrupt)
+            })
+            .await
+            .unwrap_or_else(|e| Envelope::err(e.to_string()))
+        }
+        Some("if" | "switch") => {
+            let node = node.clone();
+            let inputs = inputs.clone();
+            tokio::task::spawn_blocking(move || control::exec(&node, &inputs, interrupt))
+                .await
+                .unwrap_or_else(|e| Envelope::err(e.to_string()))
+        }
+        Some("subgraph") => subgraph::exec(node, inputs, runtime).await,
+        Some("call") => call::exec(node, inputs, runtime).await,
+        Some("map") => map::exec(node, inputs, runtime).await,
+        Some("while" | "repeat") => loops::exec(node, inputs, runtime, interrupt).await,
+        // 子图的边界节点原样转发 input_0
+        Some("input" | "output") => Envelope::ok(match inputs {
+            Value::Map(map) => map.get("input_0").cloned().unwrap_or_default(),
+            _ => Value::Null,
+        }),
+        Some(other) => Envelope::err(format!("unsupported codeType `{}`", other)),
+        None => Envelope::err(format!("node type `{}` has no codeType", node.kind)),
+    }
+}
+
+// 和 derivedNodes.js 的 onExecute 一致：rawResult 每个输出都是 result，
+// outputs 先按 output_i 再按端口名取
+fn spread(node: &Node, envelope: &Envelope) -> Vec<Value> {
+    let result = envelope.result.clone().unwrap_or_default();
+    let outputs = node.outputs();
+    if envelope.label_marked_for_outputs != "outputs" {
+        return vec![result; outputs.len()];
+    }
+    let map = match result {
+        Value::Map(map) => map,
+        _ => BTreeMap::new(),
+    };
+    outputs
+        .iter()
+        .enumerate()
+        .map(|(i, output)| {
+            map.get(&format!("output_{}", i))
+                .or_else(|| map.get(&output.name))
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+// 没连线的输入口按端口名从 bindings 里取值
+fn gather(
+    graph: &Graph,
+    node: &Node,
+    values: &HashMap<u64, Vec<Value>>,
+    bindings: &BTreeMap<String, Value>,
+) -> Value {
+    // graph/input 没有输入口，按 properties.name 从 bindings 取值当作 input_0
+    if node.code_type() == Some("input") {
+        let value = node.port_name().and_then(|name| bindings.get(name));
+        let value = value.cloned().unwrap_or_default();
+        return Value::Map(BTreeMap::from([("input_0".to_string(), value)]));
+    }
+    let inputs = graph
+        .incoming(node)
+        .into_iter()
+        .zip(node.inputs())
+        .enumerate()
+        .map(|(i, (link, input))| {
+            let value = match link {
+                Some(link) => values
+                    .get(&link.origin_id)
+                    .and_then(|v| v.get(link.origin_slot))
+                    .cloned(),
+                None => bindings.get(&input.name).cloned(),
+            };
+            (format!("input_{}", i), value.unwrap_or_default())
+        })
+        .collect();
+    Value::Map(inputs)
+}
+
+// 子进程靠 kill_on_drop 结束，解释器和 wasm 靠 interrupt
+pub async fn execute(node: &Node, inputs: &Value, runtime: &Runtime) -> (Envelope, Status) {
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let _guard = InterruptOnDrop(Arc::clone(&interrupt));
+    let work = dispatch(node, inputs, runtime, interrupt);
+    let envelope = match node.timeout() {
+        Err(e) => return (Envelope::err(e), Status::Failed),
+        Ok(Some(limit)) => match tokio::time::timeout(limit, work).await {
+            Ok(envelope) => envelope,
+            Err(_) => {
+                return (
+                    Envelope::err(format!("timed out after {}s", limit.as_secs_f64())),
+                    Status::TimedOut,
+                )
+            }
+        },
+        Ok(None) => work.await,
+    };
+    let status = if envelope.error {
+        Status::Failed
+    } else {
+        Status::Ok
+    };
+    (envelope, status)
+}
+
+// 按 properties.retry 重跑失败的节点；每次尝试都发 node-started，还要再试时发 node-retrying
+async fn execute_with_retry(
+    node: &Node,
+    inputs: &Value,
+    runtime: &Runtime,
+    run_id: u64,
+) -> (Envelope, Status, Vec<Attempt>) {
+    let policy = match retry::policy(node) {
+        Ok(policy) => policy,
+        Err(e) => return (Envelope::err(e), Status::Failed, Vec::new()),
+    };
+    let max_attempts = policy.as_ref().map_or(1, |policy| policy.max_attempts);
+    let mut attempts = Vec::new();
+    loop {
+        let attempt = attempts.len() as u32 + 1;
+        runtime.emit(Event::NodeStarted {
+            run_id,
+            id: node.id,
+            title: node.title().to_string(),
+            attempt,
+        });
+        let started = Instant::now();
+        let (envelope, status) = execute(node, inputs, runtime).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let failure = retry::failure(node, &envelope);
+        attempts.push(Attempt {
+            attempt,
+            // 退出码非 0 的 shell 结果对重试来说也是失败
+            status: if failure.is_some() && status == Status::Ok {
+                Status::Failed
+            } else {
+                status
+            },
+            error: failure.as_ref().map(|failure| failure.message.clone()),
+            duration_ms,
+        });
+        let delay = match (&policy, failure) {
+            (Some(policy), Some(failure)) if attempt < max_attempts && policy.retries(&failure) => {
+                policy.delay(attempt)
+            }
+            _ => {
+                if attempts.len() == 1 {
+                    attempts.clear();
+                }
+                return (envelope, status, attempts);
+            }
+        };
+        let last = attempts.last().unwrap();
+        runtime.emit(Event::NodeRetrying {
+            run_id,
+            id: node.id,
+            title: node.title().to_string(),
+            attempt,
+            duration_ms,
+            status: last.status,
+            error: last.error.clone().unwrap_or_default(),
+            delay_ms: delay.as_millis() as u64,
+        });
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn finish(
+    node: &Node,
+    inputs: Value,
+    envelope: Envelope,
+    duration_ms: u64,
+    status: Status,
+    attempts: Vec<Attempt>,
+) -> NodeRun {
+    let outputs = if envelope.error {
+        vec![Value::Null; node.outputs().len()]
+    } else {
+        spread(node, &envelope)
+    };
+    let inactive = if status == Status::Skipped {
+        (0..outputs.len()).collect()
+    } else if !envelope.error
+        && (control::is_control(node) || matches!(node.code_type(), Some("subgraph" | "call")))
+    {
+        control::inactive(node, &envelope)
+    } else {
+        Vec::new()
+    };
+    NodeRun {
+        id: node.id,
+        title: node.title().to_string(),
+        inputs,
+        envelope,
+        outputs,
+        duration_ms,
+        seq: 0,
+        status,
+        attempts,
+        inactive,
+    }
+}
+
+/// Runs every node of `graph` once. Nodes whose inputs are ready run concurrently,
+/// at most `graph.max_parallelism()` at a time. Unconnected input slots are fed
+/// from `bindings` by slot name.
+pub async fn run(
+    graph: &Graph,
+    runtime: &Runtime,
+    bindings: &BTreeMap<String, Value>,
+) -> Result<Report, String> {
+    run_as(next_run_id(), graph, runtime, bindings).await
+}
+
+/// Like [`run`], with a run id from [`Runs::start`].
+pub async fn run_as(
+    run_id: u64,
+    graph: &Graph,
+    runtime: &Runtime,
+    bindings: &BTreeMap<String, Value>,
+) -> Result<Report, String> {
+    let order = graph.topo_order()?;
+    let run_started = Instant::now();
+    runtime.emit(Event::RunStarted {
+        run_id,
+        nodes: graph.nodes.iter().filter(|n| n.mode != MODE_NEVER).count(),
+    });
+    let mut upstream = graph.upstream();
+    let limit = graph.max_parallelism();
+    let mut values: HashMap<u64, Vec<Value>> = HashMap::new();
+    // 没走的分支：(node id, output slot)
+    let mut inactive: HashSet<(u64, usize)> = HashSet::new();
+    let mut runs: Vec<NodeRun> = Vec::new();
+    let mut error = None;
+    // 就绪队列按 id 排序，同一张图每次的调度顺序一样
+    let mut ready: BTreeSet<u64> = upstream
+        .iter()
+        .filter(|(_, up)| up.is_empty())
+        .map(|(&id, _)| id)
+        .collect();
+    let mut running = JoinSet::new();
+    // 还在跑的任务，被取消时靠它补上报告
+    let mut pending = HashMap::new();
+    // 单步时一次只跑一个节点，跑完再停到下一个节点前面
+    let mut stepping = runtime.step;
+
+    loop {
+        while error.is_none() && running.len() < if stepping { 1 } else { limit } {
+            let id = match ready.pop_first() {
+                Some(id) => id,
+                None => break,
+            };
+            let node = graph.node(id).unwrap().clone();
+            let inputs = gather(graph, &node, &values, bindings);
+            // 连着线的输入全是 inactive 的节点不跑，它的输出也全是 inactive，一路传下去
+            let links: Vec<_> = graph.incoming(&node).into_iter().flatten().collect();
+            let skipped = !links.is_empty()
+                && links
+                    .iter()
+                    .all(|link| inactive.contains(&(link.origin_id, link.origin_slot)));
+            if let Some(debugger) = &runtime.debugger {
+                if node.mode != MODE_NEVER && !skipped && (stepping || node.breakpoint()) {
+                    let paused = Event::Paused {
+                        run_id,
+                        id,
+                        title: node.title().to_string(),
+                        inputs: inputs.clone(),
+                    };
+                    let command = tokio::select! {
+                        command = debugger.pause(runtime, paused) => command,
+                        _ = cancelled(runtime) => Command::Stop,
+                    };
+                    match command {
+                        Command::Step => stepping = true,
+                        Command::Continue => stepping = false,
+                        Command::Stop => {
+                            error = Some(format!("stopped before node {} ({})", id, node.title()));
+                            running.abort_all();
+                            break;
+                        }
+                    }
+                }
+            }
+            let runtime = runtime.clone();
+            let started = Instant::now();
+            let task = (node.clone(), inputs.clone(), started);
+            let handle = running.spawn(async move {
+                let (envelope, status, attempts) = if node.mode == MODE_NEVER {
+                    (Envelope::ok(Value::Null), Status::Ok, Vec::new())
+                } else if skipped {
+                    (Envelope::ok(Value::Null), Status::Skipped, Vec::new())
+                } else {
+                    execute_with_retry(&node, &inputs, &runtime, run_id).await
+                };
+                let duration_ms = started.elapsed().as_millis() as u64;
+                (node, inputs, envelope, duration_ms, status, attempts)
+            });
+            pending.insert(handle.id(), task);
+        }
+        let joined = tokio::select! {
+            joined = running.join_next_with_id() => joined,
+            _ = cancelled(runtime), if error.is_none() && !running.is_empty() => {
+                error = Some("run cancelled".to_string());
+                running.abort_all();
+                continue;
+            }
+        };
+        let (node, inputs, envelope, duration_ms, status, attempts) = match joined {
+            Some(Ok((id, done))) => {
+                pending.remove(&id);
+                done
+            }
+            // 任务 panic 也当成节点失败，照常往下走，RunFinished 一定会发
+            Some(Err(e)) => {
+                let (node, inputs, started) = pending.remove(&e.id()).unwrap();
+                let duration_ms = started.elapsed().as_millis() as u64;
+                let (envelope, status) = if e.is_cancelled() {
+                    (Envelope::err("cancelled"), Status::Cancelled)
+                } else {
+                    (Envelope::err(e.to_string()), Status::Failed)
+                };
+                (node, inputs, envelope, duration_ms, status, Vec::new())
+            }
+            None => break,
+        };
+        let run = finish(&node, inputs, envelope, duration_ms, status, attempts);
+        values.insert(node.id, run.outputs.clone());
+        inactive.extend(run.inactive.iter().map(|&slot| (node.id, slot)));
+        if node.mode != MODE_NEVER {
+            runtime.emit(if status == Status::Skipped {
+                Event::NodeSkipped {
+                    run_id,
+                    id: node.id,
+                    title: run.title.clone(),
+                }
+            } else if run.envelope.error {
+                Event::NodeFailed {
+                    run_id,
+                    id: node.id,
+                    title: run.title.clone(),
+                    duration_ms,
+                    status,
+                    error: run.envelope.details.clone().unwrap_or_default(),
+                }
+            } else {
+                Event::NodeFinished {
+                    run_id,
+                    id: node.id,
+                    title: run.title.clone(),
+                    duration_ms,
+                    outputs: run.outputs.iter().map(preview).collect(),
+                    inactive: run.inactive.clone(),
+                }
+            });
+        }
+        if run.envelope.error && error.is_none() {
+            error = Some(format!(
+                "node {} ({}) failed: {}",
+                node.id,
+                node.title(),
+                run.envelope.details.as_deref().unwrap_or_default()
+            ));
+            // 已经在跑的节点直接取消，子进程随 kill_on_drop 一起结束
+            running.abort_all();
+        }
+        // MODE_NEVER 的节点不出现在报告里
+        if node.mode != MODE_NEVER {
+            runs.push(NodeRun {
+                seq: runs.len() as u64,
+                ..run
+            });
+        }
+        for (&id, up) in upstream.iter_mut() {
+            if up.remove(&node.id) && up.is_empty() {
+                ready.insert(id);
+            }
+        }
+    }
+
+    // 报告按拓扑顺序排列，和完成的先后无关
+    runs.sort_by_key(|run| order.iter().position(|&id| id == run.id));
+    runtime.emit(Event::RunFinished {
+        run_id,
+        ok: error.is_none(),
+        duration_ms: run_started.elapsed().as_millis() as u64,
+        error: error.clone(),
+    });
+    Ok(Report {
+        ok: error.is_none(),
+        nodes: runs,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value as Json};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    // (link id, origin id, origin slot, target id, target slot)
+    type Link = (u64, u64, usize, u64, usize);
+
+    fn node(id: u64, code_type: &str, code: &str, inputs: usize, outputs: &[&str]) -> Json {
+        let inputs: Vec<_> = (0..inputs)
+            .map(|i| json!({"name": format!("in_{}", i), "type": "*", "link": null}))
+            .collect();
+        let outputs: Vec<_> = outputs
+            .iter()
+            .map(|name| json!({"name": name, "type": "*", "links": []}))
+            .collect();
+        json!({
+            "id": id,
+            "type": format!("code/{}", code_type),
+            "inputs": inputs,
+            "outputs": outputs,
+            "properties": {"codeType": code_type, "fn": code}
+        })
+    }
+
+    // 按 links 把两端的 link / links 填好
+    fn graph(mut nodes: Vec<Json>, links: &[Link], max_parallelism: u64) -> Graph {
+        for &(link, origin, origin_slot, target, target_slot) in links {
+            for node in nodes.iter_mut() {
+                if node["id"] == origin {
+                    node["outputs"][origin_slot]["links"]
+                        .as_array_mut()
+                        .unwrap()
+                        .push(json!(link));
+                }
+                if node["id"] == target {
+                    node["inputs"][target_slot]["link"] = json!(link);
+                }
+            }
+        }
+        let links: Vec<_> = links
+            .iter()
+            .map(|&(link, origin, origin_slot, target, target_slot)| {
+                json!([link, origin, origin_slot, target, target_slot, "*"])
+            })
+            .collect();
+        Graph::from_json(json!({
+            "nodes": nodes,
+            "links": links,
+            "extra": {"maxParallelism": max_parallelism}
+        }))
+        .unwrap()
+    }
+
+    fn test_runtime() -> (Runtime, Arc<Mutex<Vec<Event>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let runtime = Runtime {
+            cache: Arc::new(RustCache::new(
+                std::env::temp_dir().join("hp-test-rust-cache"),
+            )),
+            settings: Settings::default(),
+            events: Some(Arc::new(move |event| sink.lock().unwrap().push(event))),
+            debugger: None,
+            step: false,
+            cancel: Some(Arc::new(Cancel::default())),
+            calls: Vec::new(),
+        };
+        (runtime, events)
+    }
+
+    fn run_graph(graph: &Graph, runtime: &Runtime) -> Report {
+        run_with(graph, runtime, &BTreeMap::new())
+    }
+
+    fn run_with(graph: &Graph, runtime: &Runtime, bindings: &BTreeMap<String, Value>) -> Report {
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(run(graph, runtime, bindings))
+            .unwrap()
+    }
+
+    fn names(events: &Mutex<Vec<Event>>) -> Vec<(&'static str, u64)> {
+        events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                Event::NodeStarted { id, .. }
+                | Event::NodeFinished { id, .. }
+                | Event::NodeFailed { id, .. } => Some((event.name(), *id)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn node_run(report: &Report, id: u64) -> &NodeRun {
+        report.nodes.iter().find(|run| run.id == id).unwrap()
+    }
+
+    #[test]
+    fn dependency_order() {
+        let graph = graph(
+            vec![
+                node(
+                    4,
+                    "rustlite",
+                    "inputs.input_0 + inputs.input_1",
+                    2,
+                    &["out"],
+                ),
+                node(3, "rustlite", "inputs.input_0 * 10", 1, &["out"]),
+                node(2, "rustlite", "inputs.input_0 + 1", 1, &["out"]),
+                node(1, "rustlite", "1", 0, &["out"]),
+            ],
+            &[
+                (1, 1, 0, 2, 0),
+                (2, 2, 0, 3, 0),
+                (3, 1, 0, 4, 0),
+                (4, 3, 0, 4, 1),
+            ],
+            4,
+        );
+        let (runtime, _) = test_runtime();
+        let report = run_graph(&graph, &runtime);
+        assert!(report.ok, "{:?}", report.error);
+        let ids: Vec<_> = report.nodes.iter().map(|run| run.id).collect();
+        assert_eq!(ids, [1, 2, 3, 4]);
+        let seqs: Vec<_> = report.nodes.iter().map(|run| run.seq).collect();
+        assert_eq!(seqs, [0, 1, 2, 3]);
+        assert_eq!(node_run(&report, 4).outputs, [Value::Int(21)]);
+    }
+
+    #[test]
+    fn fan_out_runs_concurrently() {
+        let fan_out = |max_parallelism| {
+            graph(
+                vec![
+                    node(1, "rustlite", "1", 0, &["out"]),
+                    node(2, "sh", "sleep 0.3", 1, &["out"]),
+                    node(3, "sh", "sleep 0.3", 1, &["out"]),
+                    node(4, "sh", "sleep 0.3", 1, &["out"]),
+                ],
+                &[(1, 1, 0, 2, 0), (2, 1, 0, 3, 0), (3, 1, 0, 4, 0)],
+                max_parallelism,
+            )
+        };
+        let (runtime, events) = test_runtime();
+        assert!(run_graph(&fan_out(4), &runtime).ok);
+        let started = |i: usize| ("node-started", i as u64);
+        let finished = |i: usize| ("node-finished", i as u64);
+        // 三个下游在第一个跑完之前都已经开始了
+        assert_eq!(
+            names(&events)[..5],
+            [started(1), finished(1), started(2), started(3), started(4)]
+        );
+
+        let (runtime, events) = test_runtime();
+        assert!(run_graph(&fan_out(1), &runtime).ok);
+        assert_eq!(
+            names(&events),
+            [
+                started(1),
+                finished(1),
+                started(2),
+                finished(2),
+                started(3),
+                finished(3),
+                started(4),
+                finished(4)
+            ]
+        );
+    }
+
+    #[test]
+    fn failure_stops_descendants() {
+        let graph = graph(
+            vec![
+                node(1, "rustlite", "1 / 0", 0, &["out"]),
+                node(2, "rustlite", "inputs.input_0", 1, &["out"]),
+                node(3, "sh", "sleep 5", 0, &["out"]),
+            ],
+            &[(1, 1, 0, 2, 0)],
+            4,
+        );
+        let (runtime, _) = test_runtime();
+        let started = Instant::now();
+        let report = run_graph(&graph, &runtime);
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert!(!report.ok);
+        assert!(report
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("node 1 (code/rustlite) failed"));
+        assert!(node_run(&report, 1).status == Status::Failed);
+        // 下游不跑，同时在跑的节点被取消
+        assert!(report.nodes.iter().all(|run| run.id != 2));
+        assert!(node_run(&report, 3).status == Status::Cancelled);
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        let graph = graph(
+            vec![
+                node(1, "rustlite", "1", 0, &[]),
+                node(1, "rustlite", "2", 0, &[]),
+            ],
+            &[],
+            4,
+        );
+        let (runtime, _) = test_runtime();
+        let error = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(run(&graph, &runtime, &BTreeMap::new()))
+            .err()
+            .unwrap();
+        assert_eq!(error, "node id 1 is used by more than one node");
+    }
+
+    #[test]
+    fn cancel() {
+        let graph = graph(vec![node(1, "sh", "sleep 5", 0, &["out"])], &[], 4);
+        let (runtime, events) = test_runtime();
+        let cancel = Arc::clone(runtime.cancel.as_ref().unwrap());
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            cancel.cancel();
+        });
+        let started = Instant::now();
+        let report = run_graph(&graph, &runtime);
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert!(!report.ok);
+        assert_eq!(report.error.as_deref(), Some("run cancelled"));
+        assert!(node_run(&report, 1).status == Status::Cancelled);
+        let last = events.lock().unwrap().last().cloned();
+        match last {
+            Some(Event::RunFinished { ok, error, .. }) => {
+                assert!(!ok);
+                assert_eq!(error.as_deref(), Some("run cancelled"));
+            }
+            _ => panic!("the last event is not run-finished"),
+        }
+    }
+
+    // 控制节点 1 的 in_0 来自 bindings；每个输出口 i 接一个节点 10 + i，
+    // 10 后面再接 20，20 和 11 一起接到 30
+    fn branches(code_type: &str, outputs: &[&str], value: Value) -> Report {
+        let mut nodes = vec![
+            node(1, code_type, "", 1, outputs),
+            node(20, "rustlite", "inputs.input_0", 1, &["out"]),
+            node(30, "rustlite", "inputs.input_1.is_none()", 2, &["out"]),
+        ];
+        let mut links = vec![
+            (100, 10, 0, 20, 0),
+            (101, 20, 0, 30, 0),
+            (102, 11, 0, 30, 1),
+        ];
+        for slot in 0..outputs.len() {
+            let id = 10 + slot as u64;
+            nodes.push(node(id, "rustlite", "inputs.input_0", 1, &["out"]));
+            links.push((id, 1, slot, id, 0));
+        }
+        let (runtime, _) = test_runtime();
+        let bindings = BTreeMap::from([("in_0".to_string(), value)]);
+        let report = run_with(&graph(nodes, &links, 4), &runtime, &bindings);
+        assert!(report.ok, "{:?}", report.error);
+        report
+    }
+
+    fn skipped(report: &Report) -> Vec<u64> {
+        report
+            .nodes
+            .iter()
+            .filter(|run| run.status == Status::Skipped)
+            .map(|run| run.id)
+            .collect()
+    }
+
+    #[test]
+    fn if_then() {
+        let report = branches("if", &["then", "else"], Value::Int(7));
+        assert_eq!(node_run(&report, 1).inactive, [1]);
+        assert_eq!(node_run(&report, 10).outputs, [Value::Int(7)]);
+        assert_eq!(node_run(&report, 20).outputs, [Value::Int(7)]);
+        // 30 一个输入口还有值，照常跑，没走的分支给它的是 null
+        assert_eq!(node_run(&report, 30).outputs, [Value::Bool(true)]);
+        assert_eq!(skipped(&report), [11]);
+        assert_eq!(node_run(&report, 11).inactive, [0]);
+    }
+
+    #[test]
+    fn if_else() {
+        let report = branches("if", &["then", "else"], Value::Bool(false));
+        assert_eq!(node_run(&report, 1).inactive, [0]);
+        assert_eq!(node_run(&report, 11).outputs, [Value::Bool(false)]);
+        // 没走的分支一路传下去
+        assert_eq!(skipped(&report), [10, 20]);
+        assert_eq!(node_run(&report, 30).outputs, [Value::Bool(false)]);
+    }
+
+    #[test]
+    fn switch() {
+        let outputs = ["a", "b", "default"];
+        let report = branches("switch", &outputs, Value::String("b".to_string()));
+        assert_eq!(node_run(&report, 1).inactive, [0, 2]);
+        assert_eq!(
+            node_run(&report, 11).outputs,
+            [Value::String("b".to_string())]
+        );
+        assert_eq!(skipped(&report), [10, 12, 20]);
+
+        let report = branches("switch", &outputs, Value::String("zzz".to_string()));
+        assert_eq!(node_run(&report, 1).inactive, [0, 1]);
+        assert_eq!(
+            node_run(&report, 12).outputs,
+            [Value::String("zzz".to_string())]
+        );
+        assert_eq!(skipped(&report), [10, 11, 20, 30]);
+    }
+}
@@ -0,0 +1,312 @@
+// exportGraph 保存的 LiteGraph JSON（version 0.4）
+// 执行器用不到的字段放在 rest 里，读进来再写出去不丢东西
+use crate::{migrate, text_format};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Duration;
+
+/// LiteGraph `mode`: the node never runs and its outputs stay empty.
+pub const MODE_NEVER: u8 = 2;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Graph {
+    #[serde(default)]
+    pub last_node_id: u64,
+    #[serde(default)]
+    pub last_link_id: u64,
+    #[serde(default)]
+    pub nodes: Vec<Node>,
+    #[serde(default)]
+    pub links: Vec<Link>,
+    #[serde(default)]
+    pub extra: Map<String, Value>,
+    #[serde(default)]
+    pub version: f64,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Node {
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub mode: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Vec<Input>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<Output>>,
+    #[serde(default)]
+    pub properties: Map<String, Value>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Input {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: Value,
+    #[serde(default)]
+    pub link: Option<u64>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Output {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: Value,
+    #[serde(default)]
+    pub links: Option<Vec<u64>>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+}
+
+/// Stored as `[id, origin_id, origin_slot, target_id, target_slot, type]`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "LinkTuple", into = "LinkTuple")]
+pub struct Link {
+    pub id: u64,
+    pub origin_id: u64,
+    pub origin_slot: usize,
+    pub target_id: u64,
+    pub target_slot: usize,
+    pub kind: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LinkTuple(u64, u64, usize, u64, usize, Value);
+
+impl From<LinkTuple> for Link {
+    fn from(
+        LinkTuple(id, origin_id, origin_slot, target_id, target_slot, kind): LinkTuple,
+    ) -> Self {
+        Link {
+            id,
+            origin_id,
+            origin_slot,
+            target_id,
+            target_slot,
+            kind,
+        }
+    }
+}
+
+impl From<Link> for LinkTuple {
+    fn from(link: Link) -> Self {
+        LinkTuple(
+            link.id,
+            link.origin_id,
+            link.origin_slot,
+            link.target_id,
+            link.target_slot,
+            link.kind,
+        )
+    }
+}
+
+impl Node {
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.kind)
+    }
+
+    pub fn code_type(&self) -> Option<&str> {
+        self.properties.get("codeType")?.as_str()
+    }
+
+    /// `properties.breakpoint`: the debugger pauses before this node runs.
+    pub fn breakpoint(&self) -> bool {
+        self.properties
+            .get("breakpoint")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// `properties.timeout` in seconds; missing or 0 means no limit.
+    pub fn timeout(&self) -> Result<Option<Duration>, String> {
+        let raw = match self.properties.get("timeout") {
+            None | Some(Value::Null) => return Ok(None),
+            Some(raw) => raw,
+        };
+        match raw.as_f64() {
+            Some(0.0) => Ok(None),
+            // 1e20 这种能过 is_finite 的值 from_secs_f64 会 panic
+            Some(secs) if secs > 0.0 => Duration::try_from_secs_f64(secs)
+                .map(Some)
+                .map_err(|_| format!("properties.timeout {} is too large", raw)),
+            _ => Err(format!(
+                "properties.timeout must be a non-negative number of seconds, got {}",
+                raw
+            )),
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        self.properties
+            .get("fn")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    }
+
+    /// The inner graph of a `graph/subgraph` node, stored inline like LiteGraph's
+    /// own Subgraph node does.
+    pub fn subgraph(&self) -> Option<Result<Graph, String>> {
+        self.rest
+            .get("subgraph")
+            .map(|raw| Graph::from_json(raw.clone()))
+    }
+
+    /// `properties.name` of a `graph/input` / `graph/output` node: the port of the
+    /// enclosing subgraph node it stands for.
+    pub fn port_name(&self) -> Option<&str> {
+        self.properties.get("name")?.as_str()
+    }
+
+    /// `properties.path` of a `workflow/Call` node: the workflow file it runs.
+    pub fn workflow_path(&self) -> Option<&str> {
+        self.properties
+            .get("path")
+            .and_then(Value::as_str)
+            .filter(|path| !path.trim().is_empty())
+    }
+
+    /// `properties.bodyType` of a `control/Map` node: `subgraph`, or the codeType
+    /// its `fn` runs as.
+    pub fn body_type(&self) -> Option<&str> {
+        self.properties
+            .get("bodyType")
+            .and_then(Value::as_str)
+            .filter(|kind| !kind.is_empty())
+    }
+
+    pub fn inputs(&self) -> &[Input] {
+        self.inputs.as_deref().unwrap_or_default()
+    }
+
+    pub fn outputs(&self) -> &[Output] {
+        self.outputs.as_deref().unwrap_or_default()
+    }
+}
+
+impl Graph {
+    /// Reads a graph that is already in the current hp format, e.g. one sent by
+    /// the webview or nested in another graph.
+    pub fn from_json(raw: Value) -> Result<Graph, String> {
+        serde_json::from_value(raw).map_err(|e| format!("invalid graph: {}", e))
+    }
+
+    /// Reads a workflow file (JSON or YAML), upgrading it to the current hp format.
+    pub fn read(path: &Path) -> Result<Graph, String> {
+        Graph::from_json(migrate::upgrade(text_format::read(path)?)?)
+    }
+
+    pub fn node(&self, id: u64) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn link(&self, id: u64) -> Option<&Link> {
+        self.links.iter().find(|link| link.id == id)
+    }
+
+    /// Links feeding `node`, indexed by target slot.
+    pub fn incoming(&self, node: &Node) -> Vec<Option<&Link>> {
+        node.inputs()
+            .iter()
+            .map(|input| input.link.and_then(|id| self.link(id)))
+            .collect()
+    }
+
+    /// Upstream node ids of every node. Dangling links are ignored.
+    pub fn upstream(&self) -> BTreeMap<u64, BTreeSet<u64>> {
+        let mut upstream: BTreeMap<u64, BTreeSet<u64>> = self
+            .nodes
+            .iter()
+            .map(|node| (node.id, BTreeSet::new()))
+            .collect();
+        for link in &self.links {
+            if self.node(link.origin_id).is_none() {
+                continue;
+            }
+            if let Some(up) = upstream.get_mut(&link.target_id) {
+                up.insert(link.origin_id);
+            }
+        }
+        upstream
+    }
+
+    /// `extra.maxParallelism`, or the number of CPUs when unset.
+    pub fn max_parallelism(&self) -> usize {
+        match self.extra.get("maxParallelism").and_then(Value::as_u64) {
+            Some(n) if n > 0 => n as usize,
+            _ => std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+        }
+    }
+
+    /// The first node id used by more than one node, if any.
+    pub fn duplicate_id(&self) -> Option<u64> {
+        let mut seen = BTreeSet::new();
+        self.nodes
+            .iter()
+            .map(|node| node.id)
+            .find(|&id| !seen.insert(id))
+    }
+
+    /// Node ids in dependency order. Ties are broken by id so the order is stable.
+    pub fn topo_order(&self) -> Result<Vec<u64>, String> {
+        // id 重复时连线指向哪个节点说不清，别当成环报
+        if let Some(id) = self.duplicate_id() {
+            return Err(format!("node id {} is used by more than one node", id));
+        }
+        let mut indegree: BTreeMap<u64, usize> =
+            self.nodes.iter().map(|node| (node.id, 0)).collect();
+        let mut downstream: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        for link in &self.links {
+            // 悬空的 link 在这里忽略，由调用方报错
+            if !indegree.contains_key(&link.origin_id) || !indegree.contains_key(&link.target_id) {
+                continue;
+            }
+            *indegree.get_mut(&link.target_id).unwrap() += 1;
+            downstream
+                .entry(link.origin_id)
+                .or_default()
+                .push(link.target_id);
+        }
+        let mut ready: BTreeSet<u64> = indegree
+            .iter()
+            .filter(|(_, &n)| n == 0)
+            .map(|(&id, _)| id)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(id) = ready.pop_first() {
+            order.push(id);
+            for next in downstream.get(&id).into_iter().flatten() {
+                let n = indegree.get_mut(next).unwrap();
+                *n -= 1;
+                if *n == 0 {
+                    ready.insert(*next);
+                }
+            }
+        }
+        if order.len() < self.nodes.len() {
+            let stuck: Vec<String> = indegree
+                .iter()
+                .filter(|(id, _)| !order.contains(id))
+                .map(|(id, _)| id.to_string())
+                .collect();
+            return Err(format!(
+                "graph has a cycle through nodes {}",
+                stuck.join(", ")
+            ));
+        }
+        Ok(order)
+    }
+}
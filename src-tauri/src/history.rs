@@ -0,0 +1,657 @@
+// 运行记录：每次执行整张图都写进 app 数据目录下的 SQLite（history.sqlite）
+// 一次运行一行 runs，每个节点一行 node_runs；inputs / outputs 存带标签的 JSON（见 value.rs）
+use crate::executor::{Attempt, NodeRun, Report, Status};
+use crate::graph::Graph;
+use crate::value::Value;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// UPGRADES[n] 把 user_version n 升级到 n + 1，0 是新建的空库
+const UPGRADES: &[&str] = &[
+    "
+CREATE TABLE runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    graph_hash TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    finished_at INTEGER NOT NULL,
+    ok INTEGER NOT NULL,
+    error TEXT
+);
+CREATE INDEX runs_started_at ON runs (started_at);
+CREATE TABLE node_runs (
+    run_id INTEGER NOT NULL REFERENCES runs (id) ON DELETE CASCADE,
+    node_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    inputs TEXT NOT NULL,
+    outputs TEXT NOT NULL,
+    stdout TEXT,
+    stderr TEXT,
+    error TEXT,
+    duration_ms INTEGER NOT NULL,
+    PRIMARY KEY (run_id, node_id)
+);
+",
+    // 回放要用：跑的时候的图，以及节点完成的先后
+    "
+ALTER TABLE runs ADD COLUMN graph TEXT;
+ALTER TABLE node_runs ADD COLUMN seq INTEGER;
+",
+    // 重试过的节点每次尝试一行，只跑了一次的节点没有
+    "
+CREATE TABLE attempts (
+    run_id INTEGER NOT NULL REFERENCES runs (id) ON DELETE CASCADE,
+    node_id INTEGER NOT NULL,
+    attempt INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    duration_ms INTEGER NOT NULL,
+    PRIMARY KEY (run_id, node_id, attempt)
+);
+",
+    // If / Switch：节点状态（skipped 之类）和没走的输出口
+    "
+ALTER TABLE node_runs ADD COLUMN status TEXT;
+ALTER TABLE node_runs ADD COLUMN inactive TEXT;
+",
+];
+
+const SCHEMA_VERSION: i64 = UPGRADES.len() as i64;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub id: i64,
+    pub graph_hash: String,
+    /// Milliseconds since the Unix epoch.
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub ok: bool,
+    pub error: Option<String>,
+    pub node_count: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeRecord {
+    pub node_id: u64,
+    pub title: String,
+    pub inputs: Value,
+    pub outputs: Vec<Value>,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    /// Every attempt when the node was retried; empty when it ran once.
+    pub attempts: Vec<Attempt>,
+    /// `None` for runs recorded before statuses were stored.
+    pub status: Option<Status>,
+    /// Output slots that carried nothing; see `NodeRun::inactive`.
+    pub inactive: Vec<usize>,
+}
+
+#[derive(Serialize)]
+pub struct RunDetails {
+    pub run: RunSummary,
+    pub nodes: Vec<NodeRecord>,
+}
+
+/// Everything needed to replay a run: the graph as it ran and its nodes in the
+/// order they finished. `graph` is `None` for runs recorded before it was stored.
+pub struct Trace {
+    pub run: RunSummary,
+    pub graph: Option<Graph>,
+    pub steps: Vec<NodeRecord>,
+}
+
+/// Which runs `History::prune` keeps. Unset limits keep everything.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Retention {
+    /// Keep at most this many of the newest runs.
+    pub keep_last: Option<u64>,
+    /// Delete runs that started more than this many days ago.
+    pub max_age_days: Option<u64>,
+}
+
+pub struct History {
+    conn: Mutex<Connection>,
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Identifies a graph across runs; the same saved file always hashes the same.
+pub fn graph_hash(graph: &Graph) -> String {
+    let bytes = serde_json::to_vec(graph).unwrap_or_default();
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// shell 节点的 stdout / stderr 在结果里；其他节点的 print / println! 在 envelope.stdout 里，写到 stderr 的在 envelope.stderr 里
+fn streams(run: &NodeRun, is_shell: bool) -> (Option<String>, Option<String>) {
+    let text = |key: &str| match &run.envelope.result {
+        Some(Value::Map(map)) => match map.get(key) {
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(Value::Bytes(bytes)) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        },
+        _ => None,
+    };
+    if is_shell {
+        (text("stdout"), text("stderr"))
+    } else {
+        (run.envelope.stdout.clone(), run.envelope.stderr.clone())
+    }
+}
+
+// status 列存 Status 序列化出来的名字，比如 timedOut
+fn status_name(status: Status) -> Result<String, String> {
+    match serde_json::to_value(status).map_err(|e| e.to_string())? {
+        serde_json::Value::String(name) => Ok(name),
+        other => Err(format!("unexpected status {}", other)),
+    }
+}
+
+fn parse_status(name: String) -> Result<Status, String> {
+    serde_json::from_value(serde_json::Value::String(name)).map_err(|e| e.to_string())
+}
+
+fn summary(row: &rusqlite::Row) -> rusqlite::Result<RunSummary> {
+    Ok(RunSummary {
+        id: row.get(0)?,
+        graph_hash: row.get(1)?,
+        started_at: row.get::<_, i64>(2)? as u64,
+        finished_at: row.get::<_, i64>(3)? as u64,
+        ok: row.get(4)?,
+        error: row.get(5)?,
+        node_count: row.get::<_, i64>(6)? as u64,
+    })
+}
+
+const SUMMARY_COLUMNS: &str = "runs.id, graph_hash, started_at, finished_at, ok, runs.error,
+    (SELECT COUNT(*) FROM node_runs WHERE node_runs.run_id = runs.id)";
+
+impl History {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("cannot open run history {}: {}", path.display(), e))?;
+        Self::init(conn, path)
+    }
+
+    // 建表 / 升级；测试里直接传内存库
+    fn init(conn: Connection, path: &Path) -> Result<Self, String> {
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+            .map_err(|e| e.to_string())?;
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "run history {} was written by a newer hp (schema {})",
+                path.display(),
+                version
+            ));
+        }
+        for upgrade in &UPGRADES[version as usize..] {
+            conn.execute_batch(&format!("BEGIN; {} COMMIT;", upgrade))
+                .map_err(|e| format!("cannot upgrade run history: {}", e))?;
+        }
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Stores a finished run and returns its id.
+    pub fn record(
+        &self,
+        graph: &Graph,
+        started_at: u64,
+        finished_at: u64,
+        report: &Report,
+    ) -> Result<i64, String> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO runs (graph_hash, graph, started_at, finished_at, ok, error) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                graph_hash(graph),
+                serde_json::to_string(graph).map_err(|e| e.to_string())?,
+                started_at as i64,
+                finished_at as i64,
+                report.ok,
+                report.error
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        let run_id = tx.last_insert_rowid();
+        for run in &report.nodes {
+            let is_shell = graph.node(run.id).and_then(|node| node.code_type()) == Some("sh");
+            let (stdout, stderr) = streams(run, is_shell);
+            let error = run
+                .envelope
+                .error
+                .then(|| run.envelope.details.clone().unwrap_or_default());
+            tx.execute(
+                "INSERT INTO node_runs (run_id, node_id, title, inputs, outputs, stdout, stderr, error, duration_ms, seq, status, inactive)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    run_id,
+                    run.id as i64,
+                    run.title,
+                    serde_json::to_string(&run.inputs).map_err(|e| e.to_string())?,
+                    serde_json::to_string(&run.outputs).map_err(|e| e.to_string())?,
+                    stdout,
+                    stderr,
+                    error,
+                    run.duration_ms as i64,
+                    run.seq as i64,
+                    status_name(run.status)?,
+                    serde_json::to_string(&run.inactive).map_err(|e| e.to_string())?
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            for attempt in &run.attempts {
+                tx.execute(
+                    "INSERT INTO attempts (run_id, node_id, attempt, status, error, duration_ms)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        run_id,
+                        run.id as i64,
+                        attempt.attempt,
+                        status_name(attempt.status)?,
+                        attempt.error,
+                        attempt.duration_ms as i64
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(run_id)
+    }
+
+    /// Newest runs first.
+    pub fn list(&self, limit: Option<u64>) -> Result<Vec<RunSummary>, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM runs ORDER BY started_at DESC, id DESC LIMIT ?1",
+                SUMMARY_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([limit.map(|n| n as i64).unwrap_or(-1)], summary)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    fn summary(conn: &Connection, id: i64) -> Result<Option<RunSummary>, String> {
+        conn.query_row(
+            &format!("SELECT {} FROM runs WHERE id = ?1", SUMMARY_COLUMNS),
+            [id],
+            summary,
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+    }
+
+    fn attempts(conn: &Connection, id: i64) -> Result<HashMap<u64, Vec<Attempt>>, String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT node_id, attempt, status, error, duration_ms
+                 FROM attempts WHERE run_id = ?1 ORDER BY node_id, attempt",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get(3)?,
+                    row.get::<_, i64>(4)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        let mut attempts: HashMap<u64, Vec<Attempt>> = HashMap::new();
+        for row in rows {
+            let (node_id, attempt, status, error, duration_ms) = row.map_err(|e| e.to_string())?;
+            attempts.entry(node_id as u64).or_default().push(Attempt {
+                attempt,
+                status: parse_status(status)?,
+                error,
+                duration_ms: duration_ms as u64,
+            });
+        }
+        Ok(attempts)
+    }
+
+    // order 是 ORDER BY 子句：详情按拓扑顺序（插入顺序），回放按完成顺序
+    fn nodes(conn: &Connection, id: i64, order: &str) -> Result<Vec<NodeRecord>, String> {
+        let mut attempts = Self::attempts(conn, id)?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT node_id, title, inputs, outputs, stdout, stderr, error, duration_ms, status, inactive
+                 FROM node_runs WHERE run_id = ?1 ORDER BY {}",
+                order
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get::<_, i64>(7)?,
+                    row.get::<_, Option<String>>(8)?,
+                    row.get::<_, Option<String>>(9)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        let mut nodes = Vec::new();
+        for row in rows {
+            let (
+                node_id,
+                title,
+                inputs,
+                outputs,
+                stdout,
+                stderr,
+                error,
+                duration_ms,
+                status,
+                inactive,
+            ) = row.map_err(|e| e.to_string())?;
+            nodes.push(NodeRecord {
+                node_id: node_id as u64,
+                title,
+                inputs: serde_json::from_str(&inputs).map_err(|e| e.to_string())?,
+                outputs: serde_json::from_str(&outputs).map_err(|e| e.to_string())?,
+                stdout,
+                stderr,
+                error,
+                duration_ms: duration_ms as u64,
+                attempts: attempts.remove(&(node_id as u64)).unwrap_or_default(),
+                status: status.map(parse_status).transpose()?,
+                inactive: match inactive {
+                    Some(inactive) => serde_json::from_str(&inactive).map_err(|e| e.to_string())?,
+                    None => Vec::new(),
+                },
+            });
+        }
+        Ok(nodes)
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<RunDetails>, String> {
+        let conn = self.conn.lock().unwrap();
+        let run = match Self::summary(&conn, id)? {
+            Some(run) => run,
+            None => return Ok(None),
+        };
+        let nodes = Self::nodes(&conn, id, "rowid")?;
+        Ok(Some(RunDetails { run, nodes }))
+    }
+
+    pub fn trace(&self, id: i64) -> Result<Option<Trace>, String> {
+        let conn = self.conn.lock().unwrap();
+        let run = match Self::summary(&conn, id)? {
+            Some(run) => run,
+            None => return Ok(None),
+        };
+        let graph: Option<String> = conn
+            .query_row("SELECT graph FROM runs WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .map_err(|e| e.to_string())?;
+        let graph = match graph {
+            Some(graph) => Some(serde_json::from_str(&graph).map_err(|e| e.to_string())?),
+            None => None,
+        };
+        // 老记录没有 seq，退回插入顺序
+        let steps = Self::nodes(&conn, id, "seq, rowid")?;
+        Ok(Some(Trace { run, graph, steps }))
+    }
+
+    /// Deletes the runs `retention` does not keep and returns how many were removed.
+    pub fn prune(&self, retention: &Retention) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap();
+        let mut removed = 0;
+        if let Some(days) = retention.max_age_days {
+            let cutoff = now_ms().saturating_sub(days.saturating_mul(24 * 60 * 60 * 1000));
+            removed += conn
+                .execute("DELETE FROM runs WHERE started_at < ?1", [cutoff as i64])
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(keep) = retention.keep_last {
+            removed += conn
+                .execute(
+                    "DELETE FROM runs WHERE id NOT IN
+                     (SELECT id FROM runs ORDER BY started_at DESC, id DESC LIMIT ?1)",
+                    [keep as i64],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Envelope;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn memory() -> History {
+        History::init(Connection::open_in_memory().unwrap(), Path::new(":memory:")).unwrap()
+    }
+
+    fn graph() -> Graph {
+        Graph::from_json(json!({
+            "nodes": [
+                {"id": 1, "type": "shell", "properties": {"codeType": "sh"}},
+                {"id": 2, "type": "code/Python", "properties": {"codeType": "python"}},
+                {"id": 3, "type": "code/Python", "properties": {"codeType": "python"}}
+            ],
+            "links": []
+        }))
+        .unwrap()
+    }
+
+    fn node_run(id: u64, seq: u64, envelope: Envelope, status: Status) -> NodeRun {
+        NodeRun {
+            id,
+            title: format!("node {}", id),
+            inputs: Value::Map(BTreeMap::from([("input_0".to_string(), Value::Int(1))])),
+            outputs: vec![envelope.result.clone().unwrap_or_default()],
+            envelope,
+            duration_ms: 5,
+            seq,
+            status,
+            attempts: Vec::new(),
+            inactive: Vec::new(),
+        }
+    }
+
+    fn report() -> Report {
+        let shell = Envelope::ok(Value::Map(BTreeMap::from([
+            ("code".to_string(), Value::Int(0)),
+            ("stdout".to_string(), Value::String("out\n".to_string())),
+            ("stderr".to_string(), Value::Bytes(b"err\n".to_vec())),
+        ])));
+        let mut python = Envelope::ok(Value::Int(2));
+        python.stdout = Some("printed\n".to_string());
+        python.stderr = Some("warning\n".to_string());
+        let mut failed = node_run(3, 1, Envelope::err("boom"), Status::Failed);
+        failed.attempts = vec![
+            Attempt {
+                attempt: 1,
+                status: Status::TimedOut,
+                error: Some("timed out after 1s".to_string()),
+                duration_ms: 1000,
+            },
+            Attempt {
+                attempt: 2,
+                status: Status::Failed,
+                error: Some("boom".to_string()),
+                duration_ms: 3,
+            },
+        ];
+        let mut skipped = node_run(2, 2, python, Status::Skipped);
+        skipped.inactive = vec![0];
+        Report {
+            ok: false,
+            // 报告按拓扑顺序，seq 是完成的先后
+            nodes: vec![node_run(1, 0, shell, Status::Ok), skipped, failed],
+            error: Some("node 3 (node 3) failed: boom".to_string()),
+        }
+    }
+
+    #[test]
+    fn record() {
+        let history = memory();
+        let id = history.record(&graph(), 10, 20, &report()).unwrap();
+        let details = history.get(id).unwrap().unwrap();
+        assert_eq!(details.run.started_at, 10);
+        assert_eq!(details.run.finished_at, 20);
+        assert!(!details.run.ok);
+        assert_eq!(details.run.node_count, 3);
+        assert_eq!(details.run.graph_hash, graph_hash(&graph()));
+
+        let ids: Vec<_> = details.nodes.iter().map(|node| node.node_id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        let [shell, python, failed] = &details.nodes[..] else {
+            unreachable!()
+        };
+        // shell 的输出从结果里取，其他节点的在 envelope 里，两个流各占一列
+        assert_eq!(shell.stdout.as_deref(), Some("out\n"));
+        assert_eq!(shell.stderr.as_deref(), Some("err\n"));
+        assert_eq!(python.stdout.as_deref(), Some("printed\n"));
+        assert_eq!(python.stderr.as_deref(), Some("warning\n"));
+        assert_eq!(python.outputs, [Value::Int(2)]);
+        assert!(python.status == Some(Status::Skipped));
+        assert_eq!(python.inactive, [0]);
+        assert_eq!(failed.error.as_deref(), Some("boom"));
+        assert_eq!(failed.attempts.len(), 2);
+        assert!(failed.attempts[0].status == Status::TimedOut);
+        assert_eq!(
+            shell.inputs,
+            Value::Map(BTreeMap::from([("input_0".to_string(), Value::Int(1))]))
+        );
+        assert!(history.get(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn trace() {
+        let history = memory();
+        let id = history.record(&graph(), 10, 20, &report()).unwrap();
+        let trace = history.trace(id).unwrap().unwrap();
+        assert_eq!(trace.graph.unwrap().nodes.len(), 3);
+        // 回放按完成的先后
+        let ids: Vec<_> = trace.steps.iter().map(|step| step.node_id).collect();
+        assert_eq!(ids, [1, 3, 2]);
+        assert!(history.trace(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn prune() {
+        let history = memory();
+        let now = now_ms();
+        let day = 24 * 60 * 60 * 1000;
+        let old = history
+            .record(&graph(), now - 10 * day, now, &report())
+            .unwrap();
+        let kept: Vec<_> = (0..3)
+            .map(|i| history.record(&graph(), now - i, now, &report()).unwrap())
+            .collect();
+        let by_age = Retention {
+            max_age_days: Some(7),
+            ..Retention::default()
+        };
+        assert_eq!(history.prune(&by_age).unwrap(), 1);
+        assert!(history.get(old).unwrap().is_none());
+        assert_eq!(history.prune(&Retention::default()).unwrap(), 0);
+
+        let by_count = Retention {
+            keep_last: Some(2),
+            max_age_days: Some(u64::MAX),
+        };
+        assert_eq!(history.prune(&by_count).unwrap(), 1);
+        let left: Vec<_> = history
+            .list(None)
+            .unwrap()
+            .iter()
+            .map(|run| run.id)
+            .collect();
+        assert_eq!(left, [kept[0], kept[1]]);
+        // node_runs / attempts 跟着 runs 一起删掉
+        let conn = history.conn.lock().unwrap();
+        let orphans: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM attempts WHERE run_id NOT IN (SELECT id FROM runs)",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(orphans, 0);
+    }
+
+    #[test]
+    fn upgrades() {
+        // 第一版的库，里面有一条老记录
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(UPGRADES[0]).unwrap();
+        conn.execute_batch(
+            "INSERT INTO runs (id, graph_hash, started_at, finished_at, ok) VALUES (7, 'h', 1, 2, 1);
+             INSERT INTO node_runs (run_id, node_id, title, inputs, outputs, duration_ms)
+             VALUES (7, 1, 'a', '{}', '[]', 3);
+             PRAGMA user_version = 1;",
+        )
+        .unwrap();
+        let history = History::init(conn, Path::new(":memory:")).unwrap();
+        let version: i64 = history
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+
+        let trace = history.trace(7).unwrap().unwrap();
+        assert!(trace.graph.is_none());
+        assert!(trace.steps[0].status.is_none());
+        assert!(trace.steps[0].inactive.is_empty());
+        // 升级后的表照常能写
+        history.record(&graph(), 10, 20, &report()).unwrap();
+        assert_eq!(history.list(None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn newer_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        let error = History::init(conn, Path::new(":memory:")).err().unwrap();
+        assert!(error.contains("newer hp"), "{}", error);
+    }
+}
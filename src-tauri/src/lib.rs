@@ -1,8 +1,271 @@
+use std::sync::Arc;
 use tauri;
+use tauri::{AppHandle, Emitter, Manager, State};
 
+mod body;
+mod call;
+mod cli;
+mod control;
+mod executor;
+mod graph;
+mod history;
+mod loops;
+mod map;
+mod migrate;
+mod replay;
+mod retry;
+mod rust_cache;
+mod rust_diagnostics;
+mod rust_interp;
+mod rust_node;
+mod script_export;
+mod script_node;
+mod shell_node;
+mod subgraph;
+mod text_format;
+mod validate;
+mod value;
+mod wasm_node;
+
+use history::History;
+use replay::Replay;
+use rust_cache::{CacheEntry, RustCache};
+use rust_node::SettingsStore;
+use value::{Envelope, Value};
+
+#[tauri::command]
+async fn rust_exec(
+    code: String,
+    deps: Option<Vec<String>>,
+    inputs: Value,
+    cache: State<'_, Arc<RustCache>>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Envelope, String> {
+    let deps = deps.unwrap_or_default();
+    Ok(rust_node::exec(&code, &deps, &inputs, &cache, &settings.get()).await)
+}
+
+#[tauri::command]
+fn rust_settings_get(settings: State<'_, SettingsStore>) -> rust_node::Settings {
+    settings.get()
+}
+
+#[tauri::command]
+fn rust_settings_set(
+    value: rust_node::Settings,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    settings.set(value)
+}
+
+#[tauri::command]
+async fn rust_eval(code: String, inputs: Value) -> Result<Envelope, String> {
+    tauri::async_runtime::spawn_blocking(move || rust_interp::exec(&code, &inputs))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn wasm_exec(
+    code: String,
+    inputs: Value,
+    properties: Option<serde_json::Value>,
+) -> Result<Envelope, String> {
+    let limits = wasm_node::Limits::from_properties(&properties.unwrap_or_default());
+    tauri::async_runtime::spawn_blocking(move || wasm_node::exec(&code, &inputs, limits))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn shell_exec(command: String, inputs: Value) -> Result<Envelope, String> {
+    Ok(shell_node::exec(&command, &inputs).await)
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphRun {
+    #[serde(flatten)]
+    report: executor::Report,
+    /// Id of the run in the history, for `replay_open`.
+    history_id: Option<i64>,
+    /// Set when the run could not be recorded in the history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+}
+
+/// `path` is the file the canvas was opened from or saved to; `workflow/Call`
+/// paths are relative to it.
+#[tauri::command]
+async fn graph_run(
+    app: AppHandle,
+    graph: serde_json::Value,
+    step: Option<bool>,
+    path: Option<String>,
+    cache: State<'_, Arc<RustCache>>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, History>,
+    runs: State<'_, executor::Runs>,
+) -> Result<GraphRun, String> {
+    let graph = graph::Graph::from_json(graph)?;
+    // 每次运行自己的 Cancel / Debugger，按 run id 找，几次运行互不影响
+    let (run_id, cancel, debugger) = runs.start();
+    let runtime = executor::Runtime {
+        cache: Arc::clone(&cache),
+        settings: settings.get(),
+        // 执行事件转成 Tauri 事件：run-started / node-started / node-finished / node-failed / run-finished
+        events: Some(Arc::new(move |event: executor::Event| {
+            let _ = app.emit(event.name(), event);
+        })),
+        debugger: Some(debugger),
+        step: step.unwrap_or(false),
+        cancel: Some(cancel),
+        // 没存过的图没有路径，只能调用绝对路径
+        calls: path
+            .and_then(|path| std::fs::canonicalize(path).ok())
+            .into_iter()
+            .collect(),
+    };
+    let started_at = history::now_ms();
+    let report = executor::run_as(run_id, &graph, &runtime, &Default::default()).await;
+    runs.finish(run_id);
+    let report = report?;
+    // 记录失败不影响这次运行的结果，GUI 没有控制台，告诉前端
+    let (history_id, warning) = match history.record(&graph, started_at, history::now_ms(), &report)
+    {
+        Ok(id) => (Some(id), None),
+        Err(e) => (None, Some(format!("failed to record run: {}", e))),
+    };
+    Ok(GraphRun {
+        report,
+        history_id,
+        warning,
+    })
+}
+
+/// Resumes run `run_id` paused at a breakpoint: `step`, `continue` or `stop`.
 #[tauri::command]
-fn rust_exec(code: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", code)
+fn graph_debug(run_id: u64, command: executor::Command, runs: State<'_, executor::Runs>) {
+    runs.debug(run_id, command);
+}
+
+/// Cancels run `run_id`: child processes are killed and interpreters interrupted.
+#[tauri::command]
+fn graph_cancel(run_id: u64, runs: State<'_, executor::Runs>) {
+    runs.cancel(run_id);
+}
+
+/// Past runs, newest first.
+#[tauri::command]
+fn history_list(
+    limit: Option<u64>,
+    history: State<'_, History>,
+) -> Result<Vec<history::RunSummary>, String> {
+    history.list(limit)
+}
+
+/// Per-node inputs, outputs, streams and timing of one run.
+#[tauri::command]
+fn history_get(
+    id: i64,
+    history: State<'_, History>,
+) -> Result<Option<history::RunDetails>, String> {
+    history.get(id)
+}
+
+/// Starts replaying a recorded run, with the cursor after its last node.
+#[tauri::command]
+fn replay_open(
+    run_id: i64,
+    history: State<'_, History>,
+    replay: State<'_, Replay>,
+) -> Result<replay::Frame, String> {
+    replay.open(&history, run_id)
+}
+
+/// Moves the replay cursor by `delta` nodes (negative steps back).
+#[tauri::command]
+fn replay_step(delta: i64, replay: State<'_, Replay>) -> Result<replay::Frame, String> {
+    replay.step(delta)
+}
+
+#[tauri::command]
+fn replay_seek(cursor: usize, replay: State<'_, Replay>) -> Result<replay::Frame, String> {
+    replay.seek(cursor)
+}
+
+#[tauri::command]
+fn replay_close(replay: State<'_, Replay>) {
+    replay.close();
+}
+
+/// Deletes old runs; returns how many were removed.
+#[tauri::command]
+fn history_prune(
+    retention: history::Retention,
+    history: State<'_, History>,
+) -> Result<usize, String> {
+    history.prune(&retention)
+}
+
+/// Upgrades a graph read from disk to the current format, stamping `extra.hpFormat`.
+#[tauri::command]
+fn migrate_graph(graph: serde_json::Value) -> Result<serde_json::Value, String> {
+    migrate::upgrade(graph)
+}
+
+/// Renders a graph in the diff-friendly YAML format.
+#[tauri::command]
+fn graph_to_text(graph: serde_json::Value) -> Result<String, String> {
+    let graph: graph::Graph = serde_json::from_value(graph).map_err(|e| e.to_string())?;
+    text_format::to_text(&graph)
+}
+
+#[tauri::command]
+fn graph_from_text(text: String) -> Result<serde_json::Value, String> {
+    text_format::from_text(&text)
+}
+
+/// Parameters and results a workflow file declares, for syncing a `workflow/Call`
+/// node's ports. A relative `path` is resolved against `caller`.
+#[tauri::command]
+fn workflow_signature(path: String, caller: Option<String>) -> Result<call::Signature, String> {
+    let path = call::resolve(&path, caller.as_deref().map(std::path::Path::new))?;
+    Ok(call::signature(&call::load(&path)?))
+}
+
+/// Compiles a graph into a standalone bash or Python script.
+#[tauri::command]
+fn compile_graph(
+    graph: serde_json::Value,
+    target: String,
+) -> Result<script_export::Compiled, String> {
+    let graph = graph::Graph::from_json(graph)?;
+    script_export::compile(&graph, script_export::Target::parse(&target)?)
+}
+
+#[tauri::command]
+async fn validate_graph(graph: serde_json::Value) -> Vec<validate::Finding> {
+    validate::validate(graph).await
+}
+
+#[tauri::command]
+fn rust_cache_list(cache: State<'_, Arc<RustCache>>) -> Result<Vec<CacheEntry>, String> {
+    cache.list()
+}
+
+#[tauri::command]
+fn rust_cache_purge(
+    keys: Option<Vec<String>>,
+    cache: State<'_, Arc<RustCache>>,
+) -> Result<usize, String> {
+    cache.purge(keys)
+}
+
+/// Headless subcommands such as `hp run flow.json`; `None` means start the app
+/// (no arguments).
+pub fn cli(args: &[String]) -> Option<i32> {
+    cli::main(args)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -15,7 +278,41 @@ pub fn run() {
         .plugin(tauri_plugin_python::init_and_register(vec![
             "python_exec"
         ]))
-        .invoke_handler(tauri::generate_handler![rust_exec])
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            app.manage(Arc::new(RustCache::new(data_dir.join("rust-cache"))));
+            app.manage(SettingsStore::load(data_dir.join("rust-node.json")));
+            app.manage(History::open(&data_dir.join("history.sqlite"))?);
+            app.manage(Replay::default());
+            app.manage(executor::Runs::default());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            rust_exec,
+            rust_eval,
+            wasm_exec,
+            shell_exec,
+            graph_run,
+            graph_debug,
+            graph_cancel,
+            validate_graph,
+            migrate_graph,
+            graph_to_text,
+            graph_from_text,
+            compile_graph,
+            workflow_signature,
+            history_list,
+            history_get,
+            history_prune,
+            replay_open,
+            replay_step,
+            replay_seek,
+            replay_close,
+            rust_cache_list,
+            rust_cache_purge,
+            rust_settings_get,
+            rust_settings_set
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
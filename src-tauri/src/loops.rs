@@ -0,0 +1,162 @@
+// 循环节点：图里不许连环，要循环就用 While / Repeat
+// input_0 是初始状态，每一轮循环体拿到上一轮的状态（和下标），返回下一轮的状态；
+// 循环体见 body.rs，子图的 graph/input state / index 进，graph/output state 出
+// While：properties.condition 是 rustlite 表达式（inputs.input_0 状态、inputs.input_1 下标），
+// 每轮之前算一次，为假就停；留空时看状态本身真不真
+// Repeat：跑 properties.count 轮
+// 两种都受 properties.maxIterations 限制，超了算失败
+use crate::body;
+use crate::control;
+use crate::executor::Runtime;
+use crate::graph::Node;
+use crate::rust_interp;
+use crate::value::{Envelope, Value};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// Iterations allowed when `properties.maxIterations` is not set.
+pub const DEFAULT_MAX_ITERATIONS: u64 = 1000;
+
+pub fn is_loop(node: &Node) -> bool {
+    matches!(node.code_type(), Some("while" | "repeat"))
+}
+
+/// `properties.maxIterations`, or [`DEFAULT_MAX_ITERATIONS`].
+pub fn max_iterations(node: &Node) -> Result<u64, String> {
+    match node.properties.get("maxIterations") {
+        None | Some(serde_json::Value::Null) => Ok(DEFAULT_MAX_ITERATIONS),
+        Some(raw) => match raw.as_u64() {
+            Some(n) if n > 0 => Ok(n),
+            _ => Err(format!(
+                "properties.maxIterations must be a positive integer, got {}",
+                raw
+            )),
+        },
+    }
+}
+
+/// `properties.count` of a Repeat node.
+pub fn count(node: &Node) -> Result<u64, String> {
+    let raw = node.properties.get("count").cloned().unwrap_or_default();
+    raw.as_u64().ok_or_else(|| {
+        format!(
+            "properties.count must be a non-negative integer, got {}",
+            raw
+        )
+    })
+}
+
+/// `properties.condition` of a While node.
+pub fn condition(node: &Node) -> &str {
+    node.properties
+        .get("condition")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+}
+
+// 条件在 blocking 线程上跑，节点被 drop（超时、取消）时 interrupt 让解释器停下
+async fn holds(
+    node: &Node,
+    state: &Value,
+    index: usize,
+    interrupt: &Arc<AtomicBool>,
+) -> Result<bool, String> {
+    let code = condition(node).to_string();
+    if code.trim().is_empty() {
+        return Ok(control::truthy(state));
+    }
+    let inputs = Value::Map(BTreeMap::from([
+        ("input_0".to_string(), state.clone()),
+        ("input_1".to_string(), Value::Int(index as i64)),
+    ]));
+    let interrupt = Arc::clone(interrupt);
+    let envelope = tokio::task::spawn_blocking(move || {
+        rust_interp::exec_interruptible(&code, &inputs, interrupt)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if envelope.error {
+        return Err(format!(
+            "condition: {}",
+            envelope.details.unwrap_or_default()
+        ));
+    }
+    Ok(control::truthy(&envelope.result.unwrap_or_default()))
+}
+
+/// Runs a `control/While` or `control/Repeat` node.
+pub fn exec<'a>(
+    node: &'a Node,
+    inputs: &'a Value,
+    runtime: &'a Runtime,
+    interrupt: Arc<AtomicBool>,
+) -> Pin<Box<dyn Future<Output = Envelope> + Send + 'a>> {
+    // 循环体里还可以有循环，装箱断开类型环
+    Box::pin(run(node, inputs, runtime, interrupt))
+}
+
+async fn run(
+    node: &Node,
+    inputs: &Value,
+    runtime: &Runtime,
+    interrupt: Arc<AtomicBool>,
+) -> Envelope {
+    match iterate(node, inputs, runtime, &interrupt).await {
+        Ok((state, iterations)) => Envelope {
+            label_marked_for_outputs: "outputs".to_string(),
+            ..Envelope::ok(Value::Map(BTreeMap::from([
+                ("output_0".to_string(), state),
+                ("output_1".to_string(), Value::Int(iterations as i64)),
+            ])))
+        },
+        Err(e) => Envelope::err(e),
+    }
+}
+
+async fn iterate(
+    node: &Node,
+    inputs: &Value,
+    runtime: &Runtime,
+    interrupt: &Arc<AtomicBool>,
+) -> Result<(Value, u64), String> {
+    let mut state = match inputs {
+        Value::Map(map) => map.get("input_0").cloned().unwrap_or_default(),
+        _ => Value::Null,
+    };
+    let limit = max_iterations(node)?;
+    // Repeat 的轮数先定下来，While 每轮看条件
+    let count = match node.code_type() {
+        Some("repeat") => {
+            let count = count(node)?;
+            if count > limit {
+                return Err(format!("count {} is over maxIterations {}", count, limit));
+            }
+            Some(count)
+        }
+        _ => None,
+    };
+    let body = body::parse(node)?;
+    let mut index = 0;
+    loop {
+        let more = match count {
+            Some(count) => index < count,
+            None => holds(node, &state, index as usize, interrupt).await?,
+        };
+        if !more {
+            return Ok((state, index));
+        }
+        if index == limit {
+            return Err(format!(
+                "loop still running after maxIterations ({}) iterations",
+                limit
+            ));
+        }
+        state = body::run(&body, state, index as usize, ("state", "state"), runtime)
+            .await
+            .map_err(|e| format!("iteration {}: {}", index, e))?;
+        index += 1;
+    }
+}
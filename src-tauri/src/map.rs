@@ -0,0 +1,118 @@
+// Map 节点：input_0 是列表，每个元素跑一遍循环体，结果收成列表
+// 循环体见 body.rs：子图的 graph/input item / index 进，graph/output result 出；
+// 普通节点 input_0 是元素、input_1 是下标
+// 输出：results 是结果列表，errors 是 [{index, error}]；某个元素失败不影响其他元素
+use crate::body;
+use crate::executor::Runtime;
+use crate::graph::Node;
+use crate::value::{Envelope, Value};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// `properties.concurrency`: elements running at once; missing or 0 means the
+/// number of CPUs.
+fn concurrency(node: &Node) -> usize {
+    match node
+        .properties
+        .get("concurrency")
+        .and_then(serde_json::Value::as_u64)
+    {
+        Some(n) if n > 0 => n as usize,
+        _ => std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+    }
+}
+
+/// `properties.ordered`, on by default: results follow the input order and a
+/// failed element leaves `null` in its place. Off, results come in the order
+/// elements finish and failed ones are left out.
+fn ordered(node: &Node) -> bool {
+    node.properties
+        .get("ordered")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(true)
+}
+
+/// Runs a `control/Map` node.
+pub fn exec<'a>(
+    node: &'a Node,
+    inputs: &'a Value,
+    runtime: &'a Runtime,
+) -> Pin<Box<dyn Future<Output = Envelope> + Send + 'a>> {
+    // 循环体里还可以有 Map / 子图，装箱断开类型环
+    Box::pin(run(node, inputs, runtime))
+}
+
+async fn run(node: &Node, inputs: &Value, runtime: &Runtime) -> Envelope {
+    let items = match inputs {
+        Value::Map(map) => match map.get("input_0") {
+            Some(Value::List(items)) => items.clone(),
+            Some(Value::Null) | None => Vec::new(),
+            Some(other) => {
+                return Envelope::err(format!(
+                    "Map expects a list, got {}",
+                    serde_json::to_string(other).unwrap_or_default()
+                ))
+            }
+        },
+        _ => Vec::new(),
+    };
+    let body = match body::parse(node) {
+        Ok(body) => Arc::new(body),
+        Err(e) => return Envelope::err(e),
+    };
+    let limit = concurrency(node);
+    let mut pending = items.into_iter().enumerate();
+    // 任务 drop 时（超时、取消）JoinSet 把还在跑的元素一起 abort
+    let mut running = JoinSet::new();
+    let mut finished = Vec::new();
+    loop {
+        while running.len() < limit {
+            let Some((index, item)) = pending.next() else {
+                break;
+            };
+            let body = Arc::clone(&body);
+            let runtime = runtime.clone();
+            running.spawn(async move {
+                let result = body::run(&body, item, index, ("item", "result"), &runtime).await;
+                (index, result)
+            });
+        }
+        match running.join_next().await {
+            Some(Ok(done)) => finished.push(done),
+            Some(Err(e)) => return Envelope::err(e.to_string()),
+            None => break,
+        }
+    }
+    let ordered = ordered(node);
+    if ordered {
+        finished.sort_by_key(|(index, _)| *index);
+    }
+    let mut results = Vec::new();
+    let mut errors = Vec::new();
+    for (index, result) in finished {
+        match result {
+            Ok(value) => results.push(value),
+            Err(error) => {
+                if ordered {
+                    results.push(Value::Null);
+                }
+                errors.push(Value::Map(BTreeMap::from([
+                    ("index".to_string(), Value::Int(index as i64)),
+                    ("error".to_string(), Value::String(error)),
+                ])));
+            }
+        }
+    }
+    Envelope {
+        label_marked_for_outputs: "outputs".to_string(),
+        ..Envelope::ok(Value::Map(BTreeMap::from([
+            ("output_0".to_string(), Value::List(results)),
+            ("output_1".to_string(), Value::List(errors)),
+        ])))
+    }
+}
@@ -0,0 +1,166 @@
+// hp 自己的文件格式版本，记在 extra.hpFormat 里（LiteGraph 的 version 0.4 管不到节点约定）
+// 只有从磁盘读进来的图才跑迁移，按顺序把老文件升级到当前版本；比当前新的文件直接拒绝
+// 画布、内嵌子图在新建时就打上当前版本，保存时也只是打版本
+use serde_json::{json, Value};
+
+/// The format this build writes. Bump it together with a new entry in `MIGRATIONS`.
+pub const FORMAT_VERSION: u64 = 2;
+
+const FORMAT_KEY: &str = "hpFormat";
+
+// MIGRATIONS[n] 把版本 n 升级到 n + 1
+const MIGRATIONS: &[fn(&mut Value)] = &[v0_to_v1, v1_to_v2];
+const _: () = assert!(MIGRATIONS.len() as u64 == FORMAT_VERSION);
+
+// 0：没有 hpFormat 的文件
+// - one2oneTerminal 的 codeType 存的是 window.shell（bash / zsh / cmd / 空），统一成 "sh"
+// - Rust 节点补上 properties.deps
+fn v0_to_v1(graph: &mut Value) {
+    // graph["nodes"] 在没有这个键时会插进一个 null，读的时候就挂了
+    let nodes = match graph.get_mut("nodes").and_then(Value::as_array_mut) {
+        Some(nodes) => nodes,
+        None => return,
+    };
+    for node in nodes {
+        let kind = node["type"].as_str().unwrap_or_default().to_string();
+        let properties = match node["properties"].as_object_mut() {
+            Some(properties) => properties,
+            None => continue,
+        };
+        if kind.starts_with("shell/") {
+            let shell = properties.get("codeType").and_then(Value::as_str);
+            if matches!(shell, None | Some("bash" | "zsh" | "cmd" | "powershell")) {
+                properties.insert("codeType".to_string(), json!("sh"));
+            }
+        }
+        if kind == "code/Rust" && !properties.contains_key("deps") {
+            properties.insert("deps".to_string(), json!([]));
+        }
+    }
+}
+
+// 1：one2oneTerminal 在执行器里和 shell/Terminal 一样跑 fn，编辑器里却是每个输入当一条命令跑；
+// 统一成编辑器的行为，单独的 codeType "one2one"
+fn v1_to_v2(graph: &mut Value) {
+    let nodes = match graph.get_mut("nodes").and_then(Value::as_array_mut) {
+        Some(nodes) => nodes,
+        None => return,
+    };
+    for node in nodes {
+        if node["type"] != "shell/one2oneTerminal" {
+            continue;
+        }
+        if let Some(properties) = node["properties"].as_object_mut() {
+            properties.insert("codeType".to_string(), json!("one2one"));
+        }
+    }
+}
+
+/// `extra.hpFormat` of a raw graph; files saved before versioning are format 0.
+pub fn version(graph: &Value) -> u64 {
+    graph["extra"][FORMAT_KEY].as_u64().unwrap_or(0)
+}
+
+/// Marks a raw graph as `FORMAT_VERSION`, without migrating it.
+pub fn stamp(graph: &mut Value) {
+    if !graph["extra"].is_object() {
+        graph["extra"] = json!({});
+    }
+    graph["extra"][FORMAT_KEY] = json!(FORMAT_VERSION);
+}
+
+// 内嵌的子图（subgraph / Map / While / Repeat）和外层是一起存的，按外层的版本一起升级
+fn migrate(graph: &mut Value, from: u64) {
+    for migration in &MIGRATIONS[from as usize..] {
+        migration(graph);
+    }
+    stamp(graph);
+    let nodes = match graph.get_mut("nodes").and_then(Value::as_array_mut) {
+        Some(nodes) => nodes,
+        None => return,
+    };
+    for node in nodes {
+        if let Some(inner) = node.get_mut("subgraph").filter(|inner| inner.is_object()) {
+            migrate(inner, from);
+        }
+    }
+}
+
+/// Upgrades a raw graph read from disk, and the subgraphs inside it, to
+/// `FORMAT_VERSION` and stamps them.
+pub fn upgrade(mut graph: Value) -> Result<Value, String> {
+    if !graph.is_object() {
+        return Err("not a graph file: expected a JSON object".to_string());
+    }
+    let from = version(&graph);
+    if from > FORMAT_VERSION {
+        return Err(format!(
+            "graph uses format {} but this hp only reads up to format {}; please upgrade hp",
+            from, FORMAT_VERSION
+        ));
+    }
+    migrate(&mut graph, from);
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrades_v0_file() {
+        let graph = upgrade(json!({
+            "nodes": [
+                {"type": "shell/Terminal", "properties": {"codeType": "bash"}},
+                {"type": "code/Rust", "properties": {"fn": ""}},
+                {"type": "graph/subgraph", "properties": {}, "subgraph": {
+                    "nodes": [{"type": "shell/one2oneTerminal", "properties": {}}]
+                }}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(version(&graph), FORMAT_VERSION);
+        assert_eq!(graph["nodes"][0]["properties"]["codeType"], "sh");
+        assert_eq!(graph["nodes"][1]["properties"]["deps"], json!([]));
+        let inner = &graph["nodes"][2]["subgraph"];
+        assert_eq!(version(inner), FORMAT_VERSION);
+        assert_eq!(inner["nodes"][0]["properties"]["codeType"], "one2one");
+    }
+
+    #[test]
+    fn upgrades_v1_file() {
+        let graph = upgrade(json!({
+            "nodes": [
+                {"type": "shell/one2oneTerminal", "properties": {"codeType": "sh"}},
+                {"type": "shell/Terminal", "properties": {"codeType": "sh"}}
+            ],
+            "extra": {"hpFormat": 1}
+        }))
+        .unwrap();
+        assert_eq!(graph["nodes"][0]["properties"]["codeType"], "one2one");
+        assert_eq!(graph["nodes"][1]["properties"]["codeType"], "sh");
+    }
+
+    #[test]
+    fn leaves_current_file_alone() {
+        let raw = json!({
+            "nodes": [{"type": "shell/one2oneTerminal", "properties": {"codeType": "zsh"}}],
+            "extra": {"hpFormat": FORMAT_VERSION}
+        });
+        assert_eq!(upgrade(raw.clone()).unwrap(), raw);
+    }
+
+    #[test]
+    fn rejects_newer_file() {
+        let err =
+            upgrade(json!({"nodes": [], "extra": {"hpFormat": FORMAT_VERSION + 1}})).unwrap_err();
+        assert!(err.contains("please upgrade hp"), "{}", err);
+    }
+
+    #[test]
+    fn missing_nodes_key() {
+        let graph = upgrade(json!({"links": []})).unwrap();
+        assert!(graph.get("nodes").is_none());
+        assert_eq!(version(&graph), FORMAT_VERSION);
+    }
+}
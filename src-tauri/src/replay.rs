@@ -0,0 +1,138 @@
+// 回放：把运行记录按节点完成的先后一步步摆回画布，不重新执行节点
+// cursor 是已经完成的节点数：0 是还没开始，len 是跑完
+use crate::executor::Status;
+use crate::history::{History, Trace};
+use crate::value::Value;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeFrame {
+    pub id: u64,
+    pub title: String,
+    pub inputs: Value,
+    pub outputs: Vec<Value>,
+    pub error: Option<String>,
+    pub status: Option<Status>,
+}
+
+#[derive(Serialize)]
+pub struct LinkFrame {
+    pub id: u64,
+    pub value: Value,
+    /// Leaves an output slot that was inactive at this step, e.g. an untaken branch.
+    pub inactive: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Frame {
+    pub run_id: i64,
+    pub graph_hash: String,
+    pub cursor: usize,
+    pub len: usize,
+    /// The node that finished at this step; `None` before the first one.
+    pub current: Option<u64>,
+    /// Nodes that have finished by this step.
+    pub nodes: Vec<NodeFrame>,
+    /// Every link of the recorded graph with the value it carried at this step;
+    /// null while its origin has not run.
+    pub links: Vec<LinkFrame>,
+}
+
+struct Cursor {
+    trace: Trace,
+    position: usize,
+}
+
+/// The run being replayed, if any. One replay at a time, like the debugger.
+#[derive(Default)]
+pub struct Replay {
+    cursor: Mutex<Option<Cursor>>,
+}
+
+fn frame(cursor: &Cursor) -> Frame {
+    let trace = &cursor.trace;
+    let done = &trace.steps[..cursor.position];
+    let step = |id: u64| done.iter().find(|step| step.node_id == id);
+    let links = trace
+        .graph
+        .iter()
+        .flat_map(|graph| &graph.links)
+        .map(|link| {
+            let origin = step(link.origin_id);
+            LinkFrame {
+                id: link.id,
+                value: origin
+                    .and_then(|step| step.outputs.get(link.origin_slot).cloned())
+                    .unwrap_or_default(),
+                inactive: origin.is_some_and(|step| step.inactive.contains(&link.origin_slot)),
+            }
+        })
+        .collect();
+    Frame {
+        run_id: trace.run.id,
+        graph_hash: trace.run.graph_hash.clone(),
+        cursor: cursor.position,
+        len: trace.steps.len(),
+        current: done.last().map(|step| step.node_id),
+        nodes: done
+            .iter()
+            .map(|step| NodeFrame {
+                id: step.node_id,
+                title: step.title.clone(),
+                inputs: step.inputs.clone(),
+                outputs: step.outputs.clone(),
+                error: step.error.clone(),
+                status: step.status,
+            })
+            .collect(),
+        links,
+    }
+}
+
+impl Replay {
+    /// Loads run `run_id` from the history with the cursor at the end of the run.
+    pub fn open(&self, history: &History, run_id: i64) -> Result<Frame, String> {
+        let trace = history
+            .trace(run_id)?
+            .ok_or_else(|| format!("no run with id {}", run_id))?;
+        if trace.graph.is_none() {
+            return Err(format!(
+                "run {} was recorded without its graph and cannot be replayed",
+                run_id
+            ));
+        }
+        let cursor = Cursor {
+            position: trace.steps.len(),
+            trace,
+        };
+        let frame = frame(&cursor);
+        *self.cursor.lock().unwrap() = Some(cursor);
+        Ok(frame)
+    }
+
+    /// Moves the cursor to `position`, clamped to the run.
+    pub fn seek(&self, position: usize) -> Result<Frame, String> {
+        let mut cursor = self.cursor.lock().unwrap();
+        let cursor = cursor.as_mut().ok_or("no run is being replayed")?;
+        cursor.position = position.min(cursor.trace.steps.len());
+        Ok(frame(cursor))
+    }
+
+    /// Moves the cursor by `delta` nodes; negative goes back in time.
+    pub fn step(&self, delta: i64) -> Result<Frame, String> {
+        let position = {
+            let cursor = self.cursor.lock().unwrap();
+            let cursor = cursor.as_ref().ok_or("no run is being replayed")?;
+            // delta 从前端来，i64::MAX 之类的值不能溢出
+            (cursor.position as i64).saturating_add(delta).max(0) as usize
+        };
+        self.seek(position)
+    }
+
+    pub fn close(&self) {
+        self.cursor.lock().unwrap().take();
+    }
+}
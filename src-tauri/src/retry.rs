@@ -0,0 +1,227 @@
+// 节点失败重试：properties.retry 决定试几次、隔多久、哪些失败值得再试
+// { "maxAttempts": 3, "backoff": "exponential", "delay": 1, "maxDelay": 30,
+//   "exitCodes": [75], "errorPatterns": ["Connection reset"] }
+use crate::graph::Node;
+use crate::value::{Envelope, Value};
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backoff {
+    #[default]
+    Fixed,
+    /// Doubles the delay after every failed attempt, up to `maxDelay`.
+    Exponential,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct Policy {
+    /// Attempts in total, the first one included.
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+    /// Seconds to wait before the second attempt.
+    pub delay: f64,
+    /// Seconds; caps exponential backoff.
+    pub max_delay: f64,
+    /// Shell exit codes worth retrying.
+    pub exit_codes: Vec<i64>,
+    /// Substrings of the error, or of a shell node's stderr, worth retrying.
+    pub error_patterns: Vec<String>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            max_attempts: 3,
+            backoff: Backoff::Fixed,
+            delay: 1.0,
+            max_delay: 60.0,
+            exit_codes: Vec::new(),
+            error_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Why an attempt failed: the node errored, or a shell node exited non-zero.
+pub struct Failure {
+    pub exit_code: Option<i64>,
+    pub message: String,
+}
+
+// shell 节点退出码非 0 不算出错（结果照样往下传），但对重试来说是失败
+pub fn failure(node: &Node, envelope: &Envelope) -> Option<Failure> {
+    if envelope.error {
+        return Some(Failure {
+            exit_code: None,
+            message: envelope.details.clone().unwrap_or_default(),
+        });
+    }
+    if node.code_type() != Some("sh") {
+        return None;
+    }
+    let result = match &envelope.result {
+        Some(Value::Map(map)) => map,
+        _ => return None,
+    };
+    let code = match result.get("code") {
+        Some(Value::Int(code)) if *code != 0 => *code,
+        _ => return None,
+    };
+    let stderr = match result.get("stderr") {
+        Some(Value::String(text)) => text.trim().to_string(),
+        Some(Value::Bytes(bytes)) => String::from_utf8_lossy(bytes).trim().to_string(),
+        _ => String::new(),
+    };
+    Some(Failure {
+        exit_code: Some(code),
+        message: if stderr.is_empty() {
+            format!("exit code {}", code)
+        } else {
+            format!("exit code {}: {}", code, stderr)
+        },
+    })
+}
+
+/// Longest `delay`/`maxDelay` accepted: a day.
+pub const MAX_DELAY_SECS: f64 = 24.0 * 60.0 * 60.0;
+
+/// `properties.retry`, or `None` when the node is not retried.
+pub fn policy(node: &Node) -> Result<Option<Policy>, String> {
+    let raw = match node.properties.get("retry") {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(raw) => raw.clone(),
+    };
+    let policy: Policy =
+        serde_json::from_value(raw).map_err(|e| format!("invalid properties.retry: {}", e))?;
+    if policy.max_attempts == 0 {
+        return Err("properties.retry.maxAttempts must be at least 1".to_string());
+    }
+    let seconds = |secs: f64| (0.0..=MAX_DELAY_SECS).contains(&secs);
+    if !seconds(policy.delay) || !seconds(policy.max_delay) {
+        return Err(format!(
+            "properties.retry delays must be between 0 and {} seconds",
+            MAX_DELAY_SECS
+        ));
+    }
+    Ok(Some(policy))
+}
+
+impl Policy {
+    /// Whether `failure` is worth another attempt. Without `exitCodes` and
+    /// `errorPatterns` every failure is; with them only the matching ones.
+    pub fn retries(&self, failure: &Failure) -> bool {
+        if self.exit_codes.is_empty() && self.error_patterns.is_empty() {
+            return true;
+        }
+        failure
+            .exit_code
+            .is_some_and(|code| self.exit_codes.contains(&code))
+            || self
+                .error_patterns
+                .iter()
+                .any(|pattern| failure.message.contains(pattern.as_str()))
+    }
+
+    /// How long to wait after the `failed`-th attempt (1-based) failed.
+    pub fn delay(&self, failed: u32) -> Duration {
+        let secs = match self.backoff {
+            Backoff::Fixed => self.delay,
+            Backoff::Exponential => {
+                let factor = 2f64.powi(failed.saturating_sub(1).min(64) as i32);
+                (self.delay * factor).min(self.max_delay)
+            }
+        };
+        // policy() 已限了上限，这里再兜底：溢出就按最大值算，别 panic
+        Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(exit_code: Option<i64>, message: &str) -> Failure {
+        Failure {
+            exit_code,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn fixed_delay() {
+        let policy = Policy {
+            delay: 2.5,
+            ..Policy::default()
+        };
+        assert_eq!(policy.delay(1), Duration::from_millis(2500));
+        assert_eq!(policy.delay(5), Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn exponential_delay_is_capped() {
+        let policy = Policy {
+            backoff: Backoff::Exponential,
+            delay: 1.0,
+            max_delay: 10.0,
+            ..Policy::default()
+        };
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(2), Duration::from_secs(2));
+        assert_eq!(policy.delay(4), Duration::from_secs(8));
+        assert_eq!(policy.delay(5), Duration::from_secs(10));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn huge_delay_saturates() {
+        let policy = Policy {
+            backoff: Backoff::Exponential,
+            delay: 1e20,
+            max_delay: f64::INFINITY,
+            ..Policy::default()
+        };
+        assert_eq!(policy.delay(64), Duration::MAX);
+    }
+
+    #[test]
+    fn retries_everything_without_filters() {
+        let policy = Policy::default();
+        assert!(policy.retries(&failure(None, "boom")));
+        assert!(policy.retries(&failure(Some(1), "")));
+    }
+
+    #[test]
+    fn retries_only_matching_failures() {
+        let policy = Policy {
+            exit_codes: vec![75],
+            error_patterns: vec!["Connection reset".to_string()],
+            ..Policy::default()
+        };
+        assert!(policy.retries(&failure(Some(75), "")));
+        assert!(policy.retries(&failure(None, "read: Connection reset by peer")));
+        assert!(!policy.retries(&failure(Some(1), "permission denied")));
+        assert!(!policy.retries(&failure(None, "timed out")));
+    }
+
+    #[test]
+    fn policy_rejects_out_of_range_delays() {
+        let node = |retry: serde_json::Value| -> Node {
+            serde_json::from_value(serde_json::json!({
+                "id": 1,
+                "type": "code/Python",
+                "properties": { "retry": retry },
+            }))
+            .unwrap()
+        };
+        assert!(policy(&node(serde_json::json!({ "delay": 1e20 }))).is_err());
+        assert!(policy(&node(serde_json::json!({ "maxDelay": 1e20 }))).is_err());
+        assert!(policy(&node(serde_json::json!({ "delay": -1 }))).is_err());
+        assert!(policy(&node(serde_json::json!({ "maxAttempts": 0 }))).is_err());
+        let ok = policy(&node(serde_json::json!({ "delay": 5, "maxDelay": 60 })))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ok.delay(1), Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,186 @@
+// Rust 节点的编译缓存：按 源码 + rustc 版本 + 依赖 的哈希存放编译产物
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const ENTRY_FILE: &str = "entry.json";
+// 编译锁按 key 分成固定的几份，不用按 key 建了又删
+const LOCK_STRIPES: usize = 64;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+    pub key: String,
+    pub rustc: String,
+    pub dependencies: String,
+    /// First line of the node source, so entries can be told apart in a list.
+    pub preview: String,
+    pub size_bytes: u64,
+    pub created_at: u64,
+    pub last_used_at: u64,
+}
+
+pub struct RustCache {
+    root: PathBuf,
+    // 同一个 key 同时只允许一次编译；不同的 key 偶尔落在同一份上，只是多等一会
+    locks: Vec<tokio::sync::Mutex<()>>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn key(code: &str, rustc: &str, dependencies: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [code, rustc, dependencies] {
+        // 带长度前缀，避免拼接歧义
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl RustCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            locks: (0..LOCK_STRIPES).map(|_| Default::default()).collect(),
+        }
+    }
+
+    pub fn target_dir(&self) -> PathBuf {
+        self.root.join("target")
+    }
+
+    pub fn build_dir(&self, key: &str) -> PathBuf {
+        self.root.join("build").join(key)
+    }
+
+    fn entry_dir(&self, key: &str) -> PathBuf {
+        self.root.join("entries").join(key)
+    }
+
+    fn artifact_path(&self, key: &str) -> PathBuf {
+        let name = if cfg!(windows) { "node.exe" } else { "node" };
+        self.entry_dir(key).join(name)
+    }
+
+    pub fn lock(&self, key: &str) -> &tokio::sync::Mutex<()> {
+        let hash = key.bytes().fold(0usize, |hash, b| {
+            hash.wrapping_mul(31).wrapping_add(b as usize)
+        });
+        &self.locks[hash % LOCK_STRIPES]
+    }
+
+    /// Returns the cached binary for `key`, bumping its last-used time.
+    pub fn lookup(&self, key: &str) -> Option<PathBuf> {
+        let artifact = self.artifact_path(key);
+        if !artifact.is_file() {
+            return None;
+        }
+        if let Some(mut entry) = read_entry(&self.entry_dir(key)) {
+            entry.last_used_at = now();
+            let _ = write_entry(&self.entry_dir(key), &entry);
+        }
+        Some(artifact)
+    }
+
+    /// Moves a freshly built binary into the cache and records its entry.
+    pub fn store(
+        &self,
+        key: &str,
+        built: &Path,
+        code: &str,
+        rustc: &str,
+        dependencies: &str,
+    ) -> Result<PathBuf, String> {
+        let dir = self.entry_dir(key);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("create cache dir failed: {}", e))?;
+        let artifact = self.artifact_path(key);
+        std::fs::copy(built, &artifact).map_err(|e| format!("store artifact failed: {}", e))?;
+        let _ = std::fs::remove_file(built);
+
+        let entry = CacheEntry {
+            key: key.to_string(),
+            rustc: rustc.to_string(),
+            dependencies: dependencies.to_string(),
+            preview: code
+                .lines()
+                .find(|l| !l.trim().is_empty())
+                .unwrap_or("")
+                .trim()
+                .to_string(),
+            size_bytes: std::fs::metadata(&artifact).map(|m| m.len()).unwrap_or(0),
+            created_at: now(),
+            last_used_at: now(),
+        };
+        write_entry(&dir, &entry)?;
+        Ok(artifact)
+    }
+
+    pub fn list(&self) -> Result<Vec<CacheEntry>, String> {
+        let dir = self.root.join("entries");
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for item in std::fs::read_dir(&dir).map_err(|e| e.to_string())? {
+            let item = item.map_err(|e| e.to_string())?;
+            if let Some(entry) = read_entry(&item.path()) {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_used_at));
+        Ok(entries)
+    }
+
+    /// Removes the given entries, or every entry and the shared build dirs when `keys` is None.
+    pub fn purge(&self, keys: Option<Vec<String>>) -> Result<usize, String> {
+        match keys {
+            Some(keys) => {
+                let mut removed = 0;
+                for key in keys {
+                    // key 只能是十六进制，防止路径穿越
+                    if key.is_empty() || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return Err(format!("invalid cache key: {}", key));
+                    }
+                    let dir = self.entry_dir(&key);
+                    if dir.exists() {
+                        std::fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+                        removed += 1;
+                    }
+                    let _ = std::fs::remove_dir_all(self.build_dir(&key));
+                }
+                Ok(removed)
+            }
+            None => {
+                let removed = self.list()?.len();
+                for sub in ["entries", "build", "target"] {
+                    let dir = self.root.join(sub);
+                    if dir.exists() {
+                        std::fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+                    }
+                }
+                Ok(removed)
+            }
+        }
+    }
+}
+
+fn read_entry(dir: &Path) -> Option<CacheEntry> {
+    let raw = std::fs::read(dir.join(ENTRY_FILE)).ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+fn write_entry(dir: &Path, entry: &CacheEntry) -> Result<(), String> {
+    let raw = serde_json::to_vec_pretty(entry).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(ENTRY_FILE), raw).map_err(|e| e.to_string())
+}
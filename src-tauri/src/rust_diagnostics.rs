@@ -0,0 +1,146 @@
+// rustc 的 JSON 诊断信息，行列号映射回节点 properties.fn
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Same field names as Monaco's `IRange`, so the editor can use it directly.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Range {
+    pub start_line_number: u64,
+    pub start_column: u64,
+    pub end_line_number: u64,
+    pub end_column: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub message: String,
+    pub replacement: String,
+    pub range: Range,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    /// "error", "warning" or "info".
+    pub severity: String,
+    pub message: String,
+    pub code: Option<String>,
+    pub range: Range,
+    pub suggestion: Option<Suggestion>,
+}
+
+/// Maps spans of the generated `src/main.rs` onto the node source, which starts
+/// after `head_lines` wrapper lines and is `code_lines` long.
+struct SourceMap {
+    head_lines: u64,
+    code_lines: u64,
+}
+
+impl SourceMap {
+    fn range(&self, span: &Value) -> Option<Range> {
+        if span["file_name"].as_str()? != "src/main.rs" {
+            return None;
+        }
+        let line = |key: &str| {
+            span[key]
+                .as_u64()
+                .map(|l| l.saturating_sub(self.head_lines))
+        };
+        let (start, end) = (line("line_start")?, line("line_end")?);
+        let (start_column, end_column) =
+            (span["column_start"].as_u64()?, span["column_end"].as_u64()?);
+        // 落在 wrapper 里的 span（比如返回类型）收到用户代码的边界上
+        if start == 0 || start > self.code_lines {
+            let edge = if start == 0 {
+                1
+            } else {
+                self.code_lines.max(1)
+            };
+            return Some(Range {
+                start_line_number: edge,
+                start_column: 1,
+                end_line_number: edge,
+                end_column: 1,
+            });
+        }
+        Some(Range {
+            start_line_number: start,
+            start_column,
+            end_line_number: end.clamp(start, self.code_lines),
+            end_column,
+        })
+    }
+
+    fn primary(&self, message: &Value) -> Option<Range> {
+        message["spans"]
+            .as_array()?
+            .iter()
+            .filter(|span| span["is_primary"].as_bool() == Some(true))
+            .find_map(|span| self.range(span))
+    }
+
+    // 第一个带 suggested_replacement 的 span 作为快速修复
+    fn suggestion(&self, message: &Value) -> Option<Suggestion> {
+        let mut candidates = vec![message];
+        if let Some(children) = message["children"].as_array() {
+            candidates.extend(children);
+        }
+        candidates.into_iter().find_map(|item| {
+            item["spans"].as_array()?.iter().find_map(|span| {
+                let replacement = span["suggested_replacement"].as_str()?;
+                Some(Suggestion {
+                    message: item["message"].as_str().unwrap_or_default().to_string(),
+                    replacement: replacement.to_string(),
+                    range: self.range(span)?,
+                })
+            })
+        })
+    }
+}
+
+fn severity(level: &str) -> &'static str {
+    match level {
+        "warning" => "warning",
+        "note" | "help" | "failure-note" => "info",
+        _ => "error",
+    }
+}
+
+/// Parses the stdout of `cargo build --message-format=json`.
+/// Returns the mapped diagnostics and the rendered text of the errors.
+pub fn parse(stdout: &str, head_lines: usize, code: &str) -> (Vec<Diagnostic>, String) {
+    let map = SourceMap {
+        head_lines: head_lines as u64,
+        code_lines: code.lines().count() as u64,
+    };
+    let mut diagnostics = Vec::new();
+    let mut rendered = String::new();
+    for line in stdout.lines() {
+        let item: Value = match serde_json::from_str(line) {
+            Ok(item) => item,
+            Err(_) => continue,
+        };
+        if item["reason"] != "compiler-message" {
+            continue;
+        }
+        let message = &item["message"];
+        let level = message["level"].as_str().unwrap_or("error");
+        if severity(level) == "error" {
+            rendered.push_str(message["rendered"].as_str().unwrap_or_default());
+        }
+        // "aborting due to ..." 之类没有位置的汇总信息不生成 marker
+        let range = match map.primary(message) {
+            Some(range) => range,
+            None => continue,
+        };
+        diagnostics.push(Diagnostic {
+            severity: severity(level).to_string(),
+            message: message["message"].as_str().unwrap_or_default().to_string(),
+            code: message["code"]["code"].as_str().map(str::to_string),
+            range,
+            suggestion: map.suggestion(message),
+        });
+    }
+    (diagnostics, rendered)
+}
@@ -0,0 +1,1627 @@
+// Rust 语法解释器：不调用 rustc，直接解释执行 Rust 风格的节点代码
+// 支持 let / 赋值 / if / while / for / fn / 函数调用 / 字面量 / Some None Ok Err / vec 操作
+use crate::value::{Envelope, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const MAX_STEPS: u64 = 10_000_000;
+const MAX_DEPTH: usize = 256;
+// 括号、代码块嵌套太深会把栈撑爆（整个进程直接 abort），解析和执行都数着层数；
+// 解释器跑在自己的线程上，STACK_SIZE 在 debug 构建下也够这两个上限用
+const MAX_NESTING: usize = 128;
+const MAX_EVAL_NESTING: usize = 4096;
+const STACK_SIZE: usize = 128 << 20;
+const MAX_RANGE: i64 = 10_000_000;
+
+// ==================== 词法分析 ====================
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Ident(String),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Punct(&'static str),
+    Eof,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    tok: Tok,
+    line: usize,
+    column: usize,
+}
+
+// 按长度从长到短排列，保证最长匹配
+const PUNCTS: &[&str] = &[
+    "..=", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "%=",
+    "..", "+", "-", "*", "/", "%", "=", "<", ">", "!", "&", "|", "?", "(", ")", "[", "]", "{",
+    "}", ";", ":", ",", ".",
+];
+
+fn tokenize(code: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut column) = (0, 1, 1);
+
+    while i < chars.len() {
+        let c = chars[i];
+        let (start_line, start_column) = (line, column);
+        let mut advance = |n: usize, i: &mut usize| {
+            for _ in 0..n {
+                if chars.get(*i) == Some(&'\n') {
+                    line += 1;
+                    column = 1;
+                } else {
+                    column += 1;
+                }
+                *i += 1;
+            }
+        };
+
+        if c.is_whitespace() {
+            advance(1, &mut i);
+            continue;
+        }
+        // 注释
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                advance(1, &mut i);
+            }
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            advance(2, &mut i);
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                advance(1, &mut i);
+            }
+            advance(2, &mut i);
+            continue;
+        }
+
+        let tok = if c == '"' || c == '\'' {
+            // 字符串 / 字符字面量，字符按长度为 1 的字符串处理
+            advance(1, &mut i);
+            let mut value = String::new();
+            loop {
+                match chars.get(i) {
+                    None => return Err(format!("{}:{}: unterminated literal", start_line, start_column)),
+                    Some(&q) if q == c => {
+                        advance(1, &mut i);
+                        break;
+                    }
+                    Some('\\') => {
+                        let escaped = match chars.get(i + 1) {
+                            Some('n') => '\n',
+                            Some('t') => '\t',
+                            Some('r') => '\r',
+                            Some('0') => '\0',
+                            Some(&other) => other,
+                            None => '\\',
+                        };
+                        value.push(escaped);
+                        advance(2, &mut i);
+                    }
+                    Some(&other) => {
+                        value.push(other);
+                        advance(1, &mut i);
+                    }
+                }
+            }
+            Tok::Str(value)
+        } else if c.is_ascii_digit() {
+            let mut text = String::new();
+            let mut is_float = false;
+            while let Some(&d) = chars.get(i) {
+                let next = chars.get(i + 1).copied();
+                if d.is_ascii_digit() || d == '_' {
+                    if d != '_' {
+                        text.push(d);
+                    }
+                } else if d == '.' && !is_float && next.is_some_and(|n| n.is_ascii_digit()) {
+                    // 1..3 是 range，不是小数
+                    is_float = true;
+                    text.push(d);
+                } else if (d == 'e' || d == 'E') && next.is_some_and(|n| n.is_ascii_digit() || n == '-' || n == '+') {
+                    is_float = true;
+                    text.push(d);
+                    text.push(next.unwrap());
+                    advance(1, &mut i);
+                } else {
+                    break;
+                }
+                advance(1, &mut i);
+            }
+            // 类型后缀，例如 10i64 / 1.5f32
+            let mut suffix = String::new();
+            while let Some(&d) = chars.get(i) {
+                if !d.is_ascii_alphanumeric() {
+                    break;
+                }
+                suffix.push(d);
+                advance(1, &mut i);
+            }
+            if suffix.starts_with('f') {
+                is_float = true;
+            }
+            let located = |e: String| format!("{}:{}: {}", start_line, start_column, e);
+            if is_float {
+                Tok::Float(text.parse().map_err(|_| located(format!("invalid number {}", text)))?)
+            } else {
+                Tok::Int(text.parse().map_err(|_| located(format!("integer out of range {}", text)))?)
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&d) = chars.get(i) {
+                if !(d.is_alphanumeric() || d == '_') {
+                    break;
+                }
+                name.push(d);
+                advance(1, &mut i);
+            }
+            Tok::Ident(name)
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            match PUNCTS.iter().find(|p| rest.starts_with(**p)) {
+                Some(p) => {
+                    advance(p.len(), &mut i);
+                    Tok::Punct(p)
+                }
+                None => return Err(format!("{}:{}: unexpected character '{}'", start_line, start_column, c)),
+            }
+        };
+        tokens.push(Token {
+            tok,
+            line: start_line,
+            column: start_column,
+        });
+    }
+    tokens.push(Token {
+        tok: Tok::Eof,
+        line,
+        column,
+    });
+    Ok(tokens)
+}
+
+// ==================== 语法树 ====================
+#[derive(Debug)]
+enum Expr {
+    Lit(Val),
+    Var(String),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Range(Box<Expr>, Box<Expr>, bool),
+    Call(String, Vec<Expr>),
+    Method(Box<Expr>, String, Vec<Expr>),
+    Macro(String, Vec<Expr>),
+    Index(Box<Expr>, Box<Expr>),
+    Field(Box<Expr>, String),
+    List(Vec<Expr>),
+    Try(Box<Expr>),
+    Cast(Box<Expr>, String),
+    Block(Vec<Stmt>),
+    If(Box<Expr>, Vec<Stmt>, Option<Box<Expr>>),
+}
+
+#[derive(Debug)]
+enum StmtKind {
+    Let(String, Option<Expr>),
+    Assign(Expr, &'static str, Expr),
+    Expr(Expr, bool),
+    Return(Option<Expr>),
+    While(Expr, Vec<Stmt>),
+    For(String, Expr, Vec<Stmt>),
+    Fn(Rc<FnDef>),
+    Break,
+    Continue,
+}
+
+#[derive(Debug)]
+struct Stmt {
+    kind: StmtKind,
+    line: usize,
+}
+
+#[derive(Debug)]
+struct FnDef {
+    name: String,
+    params: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+// ==================== 语法分析 ====================
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn nested<T>(&mut self, f: fn(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        if self.depth >= MAX_NESTING {
+            return self.error("nesting too deep");
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn peek(&self) -> &Tok {
+        &self.tokens[self.pos].tok
+    }
+
+    fn peek_at(&self, offset: usize) -> &Tok {
+        let i = (self.pos + offset).min(self.tokens.len() - 1);
+        &self.tokens[i].tok
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.pos].line
+    }
+
+    fn next(&mut self) -> Tok {
+        let tok = self.tokens[self.pos].tok.clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, String> {
+        let token = &self.tokens[self.pos];
+        let found = match &token.tok {
+            Tok::Ident(name) => name.clone(),
+            Tok::Int(n) => n.to_string(),
+            Tok::Float(f) => f.to_string(),
+            Tok::Str(s) => format!("{:?}", s),
+            Tok::Punct(p) => p.to_string(),
+            Tok::Eof => "end of input".to_string(),
+        };
+        Err(format!("{}:{}: {}, found {}", token.line, token.column, message, found))
+    }
+
+    fn is_punct(&self, p: &str) -> bool {
+        matches!(self.peek(), Tok::Punct(q) if *q == p)
+    }
+
+    fn is_keyword(&self, k: &str) -> bool {
+        matches!(self.peek(), Tok::Ident(name) if name == k)
+    }
+
+    fn eat(&mut self, p: &str) -> bool {
+        if self.is_punct(p) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, p: &str) -> Result<(), String> {
+        if self.eat(p) {
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", p))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek().clone() {
+            Tok::Ident(name) => {
+                self.next();
+                Ok(name)
+            }
+            _ => self.error("expected identifier"),
+        }
+    }
+
+    // 类型标注只用于阅读，解释时跳过
+    fn skip_type(&mut self) {
+        let mut depth = 0i32;
+        loop {
+            match self.peek() {
+                Tok::Eof => return,
+                Tok::Punct("<") | Tok::Punct("(") | Tok::Punct("[") => depth += 1,
+                Tok::Punct(">") | Tok::Punct(")") | Tok::Punct("]") if depth > 0 => depth -= 1,
+                Tok::Punct("=") | Tok::Punct(";") | Tok::Punct(",") | Tok::Punct(")") | Tok::Punct("{")
+                    if depth == 0 =>
+                {
+                    return
+                }
+                _ => {}
+            }
+            self.next();
+        }
+    }
+
+    fn skip_generics(&mut self) -> Result<(), String> {
+        self.expect("<")?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                Tok::Punct("<") => depth += 1,
+                Tok::Punct(">") => depth -= 1,
+                Tok::Eof => return self.error("expected '>'"),
+                _ => {}
+            }
+            self.next();
+        }
+        Ok(())
+    }
+
+    fn program(&mut self) -> Result<Vec<Stmt>, String> {
+        let mut stmts = Vec::new();
+        while *self.peek() != Tok::Eof {
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        self.nested(Self::block_body)
+    }
+
+    fn block_body(&mut self) -> Result<Vec<Stmt>, String> {
+        self.expect("{")?;
+        let mut stmts = Vec::new();
+        while !self.is_punct("}") {
+            if *self.peek() == Tok::Eof {
+                return self.error("expected '}'");
+            }
+            stmts.push(self.stmt()?);
+        }
+        self.next();
+        Ok(stmts)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, String> {
+        let line = self.line();
+        let kind = match self.peek().clone() {
+            Tok::Ident(k) if k == "let" => {
+                self.next();
+                if self.is_keyword("mut") {
+                    self.next();
+                }
+                let name = self.ident()?;
+                if self.eat(":") {
+                    self.skip_type();
+                }
+                let value = if self.eat("=") { Some(self.expr()?) } else { None };
+                self.expect(";")?;
+                StmtKind::Let(name, value)
+            }
+            Tok::Ident(k) if k == "return" => {
+                self.next();
+                let value = if self.is_punct(";") || self.is_punct("}") || *self.peek() == Tok::Eof {
+                    None
+                } else {
+                    Some(self.expr()?)
+                };
+                self.eat(";");
+                StmtKind::Return(value)
+            }
+            Tok::Ident(k) if k == "while" => {
+                self.next();
+                let cond = self.expr()?;
+                StmtKind::While(cond, self.block()?)
+            }
+            Tok::Ident(k) if k == "loop" => {
+                self.next();
+                StmtKind::While(Expr::Lit(Val::Bool(true)), self.block()?)
+            }
+            Tok::Ident(k) if k == "for" => {
+                self.next();
+                let name = self.ident()?;
+                if !self.is_keyword("in") {
+                    return self.error("expected 'in'");
+                }
+                self.next();
+                let iter = self.expr()?;
+                StmtKind::For(name, iter, self.block()?)
+            }
+            Tok::Ident(k) if k == "fn" => {
+                self.next();
+                let name = self.ident()?;
+                self.expect("(")?;
+                let mut params = Vec::new();
+                while !self.eat(")") {
+                    if self.is_keyword("mut") {
+                        self.next();
+                    }
+                    params.push(self.ident()?);
+                    if self.eat(":") {
+                        self.skip_type();
+                    }
+                    if !self.is_punct(")") {
+                        self.expect(",")?;
+                    }
+                }
+                if self.eat("->") {
+                    self.skip_type();
+                }
+                let body = self.block()?;
+                StmtKind::Fn(Rc::new(FnDef { name, params, body }))
+            }
+            Tok::Ident(k) if k == "break" => {
+                self.next();
+                self.eat(";");
+                StmtKind::Break
+            }
+            Tok::Ident(k) if k == "continue" => {
+                self.next();
+                self.eat(";");
+                StmtKind::Continue
+            }
+            _ => {
+                let expr = self.expr()?;
+                let assign = ["=", "+=", "-=", "*=", "/=", "%="]
+                    .into_iter()
+                    .find(|op| self.is_punct(op));
+                if let Some(op) = assign {
+                    if !matches!(expr, Expr::Var(_) | Expr::Index(..)) {
+                        return self.error("invalid assignment target");
+                    }
+                    self.next();
+                    let value = self.expr()?;
+                    self.expect(";")?;
+                    StmtKind::Assign(expr, op, value)
+                } else {
+                    // 以块结尾的表达式（if / {}）后面不需要分号
+                    let block_like = matches!(expr, Expr::If(..) | Expr::Block(..));
+                    let semi = self.eat(";");
+                    if !semi && !block_like && !self.is_punct("}") && *self.peek() != Tok::Eof {
+                        return self.error("expected ';'");
+                    }
+                    StmtKind::Expr(expr, semi)
+                }
+            }
+        };
+        Ok(Stmt { kind, line })
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        self.nested(|p| p.binary(0))
+    }
+
+    fn binary(&mut self, min_prec: u8) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let (op, prec) = match self.peek() {
+                Tok::Punct(p @ (".." | "..=")) => (*p, 1),
+                Tok::Punct("||") => ("||", 2),
+                Tok::Punct("&&") => ("&&", 3),
+                Tok::Punct(p @ ("==" | "!=" | "<" | ">" | "<=" | ">=")) => (*p, 4),
+                Tok::Punct(p @ ("+" | "-")) => (*p, 5),
+                Tok::Punct(p @ ("*" | "/" | "%")) => (*p, 6),
+                Tok::Ident(k) if k == "as" => ("as", 7),
+                _ => break,
+            };
+            if prec < min_prec {
+                break;
+            }
+            self.next();
+            left = match op {
+                "as" => {
+                    let ty = self.ident()?;
+                    Expr::Cast(Box::new(left), ty)
+                }
+                ".." | "..=" => {
+                    let right = self.binary(prec + 1)?;
+                    Expr::Range(Box::new(left), Box::new(right), op == "..=")
+                }
+                _ => {
+                    let right = self.binary(prec + 1)?;
+                    Expr::Binary(op, Box::new(left), Box::new(right))
+                }
+            };
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Tok::Punct("-") => {
+                self.next();
+                Ok(Expr::Unary("-", Box::new(self.nested(Self::unary)?)))
+            }
+            Tok::Punct("!") => {
+                self.next();
+                Ok(Expr::Unary("!", Box::new(self.nested(Self::unary)?)))
+            }
+            // 引用与解引用在解释器里没有意义
+            Tok::Punct("&") | Tok::Punct("*") => {
+                self.next();
+                if self.is_keyword("mut") {
+                    self.next();
+                }
+                self.nested(Self::unary)
+            }
+            _ => self.postfix(),
+        }
+    }
+
+    fn args(&mut self, close: &str) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        while !self.eat(close) {
+            args.push(self.expr()?);
+            if !self.is_punct(close) {
+                self.expect(",")?;
+            }
+        }
+        Ok(args)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat("[") {
+                let index = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else if self.eat("?") {
+                expr = Expr::Try(Box::new(expr));
+            } else if self.is_punct(".") {
+                self.next();
+                let name = match self.next() {
+                    Tok::Ident(name) => name,
+                    Tok::Int(n) => n.to_string(),
+                    _ => return self.error("expected field or method name"),
+                };
+                // turbofish: x.parse::<i64>()
+                if self.eat("::") {
+                    self.skip_generics()?;
+                }
+                if self.eat("(") {
+                    expr = Expr::Method(Box::new(expr), name, self.args(")")?);
+                } else {
+                    expr = Expr::Field(Box::new(expr), name);
+                }
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        if *self.peek() == Tok::Eof {
+            return self.error("expected expression");
+        }
+        match self.next() {
+            Tok::Int(n) => Ok(Expr::Lit(Val::Int(n))),
+            Tok::Float(f) => Ok(Expr::Lit(Val::Float(f))),
+            Tok::Str(s) => Ok(Expr::Lit(Val::Str(s))),
+            Tok::Punct("(") => {
+                if self.eat(")") {
+                    return Ok(Expr::Lit(Val::Unit));
+                }
+                let inner = self.expr()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Tok::Punct("[") => Ok(Expr::List(self.args("]")?)),
+            Tok::Punct("{") => {
+                self.pos -= 1;
+                Ok(Expr::Block(self.block()?))
+            }
+            Tok::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Lit(Val::Bool(true))),
+                "false" => Ok(Expr::Lit(Val::Bool(false))),
+                "None" => Ok(Expr::Lit(Val::Opt(None))),
+                "if" => self.if_expr(),
+                _ => {
+                    // 路径：String::from、Vec::new 等
+                    let mut path = name;
+                    while self.is_punct("::") && matches!(self.peek_at(1), Tok::Ident(_)) {
+                        self.next();
+                        path = format!("{}::{}", path, self.ident()?);
+                    }
+                    if self.is_punct("!") && matches!(self.peek_at(1), Tok::Punct("(") | Tok::Punct("[")) {
+                        self.next();
+                        let close = if self.eat("(") { ")" } else {
+                            self.next();
+                            "]"
+                        };
+                        return Ok(Expr::Macro(path, self.args(close)?));
+                    }
+                    if self.eat("::") {
+                        // 泛型参数 vec_new::<i32>()
+                        self.skip_generics()?;
+                    }
+                    if self.eat("(") {
+                        return Ok(Expr::Call(path, self.args(")")?));
+                    }
+                    Ok(Expr::Var(path))
+                }
+            },
+            _ => {
+                self.pos -= 1;
+                self.error("expected expression")
+            }
+        }
+    }
+
+    fn if_expr(&mut self) -> Result<Expr, String> {
+        let cond = self.expr()?;
+        let then = self.block()?;
+        let otherwise = if self.is_keyword("else") {
+            self.next();
+            if self.is_keyword("if") {
+                self.next();
+                Some(Box::new(self.if_expr()?))
+            } else {
+                Some(Box::new(Expr::Block(self.block()?)))
+            }
+        } else {
+            None
+        };
+        Ok(Expr::If(Box::new(cond), then, otherwise))
+    }
+}
+
+// ==================== 运行时值 ====================
+#[derive(Clone, Debug, PartialEq)]
+enum Val {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    List(Vec<Val>),
+    Map(BTreeMap<String, Val>),
+    Opt(Option<Box<Val>>),
+    Res(Result<Box<Val>, Box<Val>>),
+}
+
+impl Val {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Val::Unit => "()",
+            Val::Bool(_) => "bool",
+            Val::Int(_) => "i64",
+            Val::Float(_) => "f64",
+            Val::Str(_) => "String",
+            Val::List(_) => "Vec",
+            Val::Map(_) => "Map",
+            Val::Opt(_) => "Option",
+            Val::Res(_) => "Result",
+        }
+    }
+
+    fn truthy(&self) -> Result<bool, String> {
+        match self {
+            Val::Bool(b) => Ok(*b),
+            other => Err(format!("expected bool, found {}", other.type_name())),
+        }
+    }
+
+    fn display(&self) -> String {
+        match self {
+            Val::Unit => "()".to_string(),
+            Val::Bool(b) => b.to_string(),
+            Val::Int(n) => n.to_string(),
+            Val::Float(f) => f.to_string(),
+            Val::Str(s) => s.clone(),
+            other => other.debug(),
+        }
+    }
+
+    fn debug(&self) -> String {
+        match self {
+            Val::Str(s) => format!("{:?}", s),
+            Val::List(items) => format!("[{}]", items.iter().map(Val::debug).collect::<Vec<_>>().join(", ")),
+            Val::Map(map) => format!(
+                "{{{}}}",
+                map.iter()
+                    .map(|(k, v)| format!("{:?}: {}", k, v.debug()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Val::Opt(Some(v)) => format!("Some({})", v.debug()),
+            Val::Opt(None) => "None".to_string(),
+            Val::Res(Ok(v)) => format!("Ok({})", v.debug()),
+            Val::Res(Err(v)) => format!("Err({})", v.debug()),
+            other => other.display(),
+        }
+    }
+
+    fn from_value(value: Value) -> Val {
+        match value {
+            Value::Null => Val::Opt(None),
+            Value::Bool(b) => Val::Bool(b),
+            Value::Int(n) => Val::Int(n),
+            Value::Float(f) => Val::Float(f),
+            Value::String(s) => Val::Str(s),
+            // 二进制按 Vec<u8> 处理
+            Value::Bytes(bytes) => Val::List(bytes.into_iter().map(|b| Val::Int(b as i64)).collect()),
+            Value::List(items) => Val::List(items.into_iter().map(Val::from_value).collect()),
+            Value::Map(map) => Val::Map(map.into_iter().map(|(k, v)| (k, Val::from_value(v))).collect()),
+            Value::Error(message) => Val::Res(Err(Box::new(Val::Str(message)))),
+        }
+    }
+
+    // Some(x) / Ok(x) 展开为 x，None 为 null，嵌套的 Err 变成错误值
+    fn to_value(&self) -> Value {
+        match self {
+            Val::Unit | Val::Opt(None) => Value::Null,
+            Val::Bool(b) => Value::Bool(*b),
+            Val::Int(n) => Value::Int(*n),
+            Val::Float(f) => Value::Float(*f),
+            Val::Str(s) => Value::String(s.clone()),
+            Val::List(items) => Value::List(items.iter().map(Val::to_value).collect()),
+            Val::Map(map) => Value::Map(map.iter().map(|(k, v)| (k.clone(), v.to_value())).collect()),
+            Val::Opt(Some(v)) | Val::Res(Ok(v)) => v.to_value(),
+            Val::Res(Err(e)) => Value::Error(e.display()),
+        }
+    }
+}
+
+// ==================== 预制函数库 ====================
+// (名字, 参数个数)，None 表示可变参数
+const BUILTINS: &[(&str, Option<usize>)] = &[
+    ("add", Some(2)),
+    ("subtract", Some(2)),
+    ("multiply", Some(2)),
+    ("divide", Some(2)),
+    ("pow", Some(2)),
+    ("to_uppercase", Some(1)),
+    ("to_lowercase", Some(1)),
+    ("trim", Some(1)),
+    ("len", Some(1)),
+    ("concat", Some(2)),
+    ("vec_new", Some(0)),
+    ("vec_push", Some(2)),
+    ("vec_pop", Some(1)),
+    ("vec_len", Some(1)),
+    ("vec_get", Some(2)),
+    ("and", Some(2)),
+    ("or", Some(2)),
+    ("not", Some(1)),
+    ("eq", Some(2)),
+    ("neq", Some(2)),
+    ("gt", Some(2)),
+    ("lt", Some(2)),
+    ("Some", Some(1)),
+    ("Ok", Some(1)),
+    ("Err", Some(1)),
+    ("unwrap", Some(1)),
+    ("unwrap_or", Some(2)),
+    ("is_some", Some(1)),
+    ("is_none", Some(1)),
+    ("is_ok", Some(1)),
+    ("is_err", Some(1)),
+    ("contains", Some(2)),
+    ("to_string", Some(1)),
+    ("parse", Some(1)),
+    ("abs", Some(1)),
+    ("min", Some(2)),
+    ("max", Some(2)),
+    ("println", None),
+    ("print", None),
+];
+
+// 会修改第一个参数的函数，调用后写回变量
+const MUTATING: &[&str] = &["vec_push", "vec_pop"];
+
+fn method_builtin(name: &str) -> &str {
+    match name {
+        "push" => "vec_push",
+        "pop" => "vec_pop",
+        "get" => "vec_get",
+        "to_owned" | "clone" | "iter" | "into_iter" | "as_str" => "identity",
+        other => other,
+    }
+}
+
+fn path_builtin(path: &str) -> &str {
+    match path {
+        "Vec::new" => "vec_new",
+        "String::from" | "String::new" => "to_string",
+        other => other,
+    }
+}
+
+fn num(value: &Val) -> Result<f64, String> {
+    match value {
+        Val::Int(n) => Ok(*n as f64),
+        Val::Float(f) => Ok(*f),
+        other => Err(format!("expected number, found {}", other.type_name())),
+    }
+}
+
+fn arith(op: &str, a: &Val, b: &Val) -> Result<Val, String> {
+    let overflow = || {
+        let verb = match op {
+            "+" => "add",
+            "-" => "subtract",
+            "*" => "multiply",
+            "/" => "divide",
+            _ => "calculate the remainder",
+        };
+        format!("attempt to {} with overflow", verb)
+    };
+    match (a, b) {
+        (Val::Int(x), Val::Int(y)) => {
+            let value = match op {
+                "+" => x.checked_add(*y),
+                "-" => x.checked_sub(*y),
+                "*" => x.checked_mul(*y),
+                "/" | "%" if *y == 0 => return Err("attempt to divide by zero".to_string()),
+                "/" => x.checked_div(*y),
+                "%" => x.checked_rem(*y),
+                _ => None,
+            };
+            value.map(Val::Int).ok_or_else(overflow)
+        }
+        (Val::Str(x), Val::Str(y)) if op == "+" => Ok(Val::Str(format!("{}{}", x, y))),
+        _ => {
+            let (x, y) = (num(a)?, num(b)?);
+            Ok(Val::Float(match op {
+                "+" => x + y,
+                "-" => x - y,
+                "*" => x * y,
+                "/" => x / y,
+                _ => x % y,
+            }))
+        }
+    }
+}
+
+fn compare(op: &str, a: &Val, b: &Val) -> Result<bool, String> {
+    if op == "==" {
+        // 1 == 1.0 按数值比较
+        return match (a, b) {
+            (Val::Int(_), Val::Float(_)) | (Val::Float(_), Val::Int(_)) => Ok(num(a)? == num(b)?),
+            _ => Ok(a == b),
+        };
+    }
+    if op == "!=" {
+        return compare("==", a, b).map(|eq| !eq);
+    }
+    let ordering = match (a, b) {
+        (Val::Int(x), Val::Int(y)) => x.partial_cmp(y),
+        (Val::Str(x), Val::Str(y)) => x.partial_cmp(y),
+        (Val::Bool(x), Val::Bool(y)) => x.partial_cmp(y),
+        _ => num(a)?.partial_cmp(&num(b)?),
+    };
+    let ordering = match ordering {
+        Some(ordering) => ordering,
+        None => return Ok(false),
+    };
+    Ok(match op {
+        "<" => ordering.is_lt(),
+        ">" => ordering.is_gt(),
+        "<=" => ordering.is_le(),
+        _ => ordering.is_ge(),
+    })
+}
+
+fn call_builtin(name: &str, mut args: Vec<Val>, stdout: &mut String) -> Result<Val, String> {
+    if name == "identity" && args.len() == 1 {
+        return Ok(args.remove(0));
+    }
+    let arity = match BUILTINS.iter().find(|(n, _)| *n == name) {
+        Some((_, arity)) => *arity,
+        None => return Err(format!("undefined function: {}", name)),
+    };
+    if let Some(expected) = arity {
+        if args.len() != expected {
+            return Err(format!("function {} takes {} argument(s) but {} were supplied", name, expected, args.len()));
+        }
+    }
+    let mut args = args.into_iter();
+    let mut arg = || args.next().unwrap();
+    Ok(match name {
+        "add" => arith("+", &arg(), &arg())?,
+        "subtract" => arith("-", &arg(), &arg())?,
+        "multiply" => arith("*", &arg(), &arg())?,
+        "divide" => {
+            let (x, y) = (num(&arg())?, num(&arg())?);
+            Val::Float(if y != 0.0 { x / y } else { f64::INFINITY })
+        }
+        "pow" => match (arg(), arg()) {
+            (Val::Int(x), Val::Int(y)) if (0..=u32::MAX as i64).contains(&y) => {
+                Val::Int(x.checked_pow(y as u32).ok_or("attempt to pow with overflow")?)
+            }
+            (x, y) => Val::Float(num(&x)?.powf(num(&y)?)),
+        },
+        "to_uppercase" | "to_lowercase" | "trim" => match arg() {
+            Val::Str(s) => Val::Str(match name {
+                "to_uppercase" => s.to_uppercase(),
+                "to_lowercase" => s.to_lowercase(),
+                _ => s.trim().to_string(),
+            }),
+            other => return Err(format!("{} expects String, found {}", name, other.type_name())),
+        },
+        "len" | "vec_len" => match arg() {
+            Val::Str(s) => Val::Int(s.chars().count() as i64),
+            Val::List(items) => Val::Int(items.len() as i64),
+            Val::Map(map) => Val::Int(map.len() as i64),
+            other => return Err(format!("{} of {}", name, other.type_name())),
+        },
+        "concat" => Val::Str(format!("{}{}", arg().display(), arg().display())),
+        "vec_new" => Val::List(Vec::new()),
+        "vec_push" => match arg() {
+            Val::List(mut items) => {
+                items.push(arg());
+                Val::List(items)
+            }
+            other => return Err(format!("vec_push on {}", other.type_name())),
+        },
+        "vec_pop" => match arg() {
+            Val::List(mut items) => {
+                let popped = items.pop().map(Box::new);
+                // 返回 (新 vec, 弹出值)，由调用方写回变量
+                Val::List(vec![Val::List(items), Val::Opt(popped)])
+            }
+            other => return Err(format!("vec_pop on {}", other.type_name())),
+        },
+        "vec_get" => match (arg(), arg()) {
+            (Val::List(items), Val::Int(i)) => {
+                Val::Opt(usize::try_from(i).ok().and_then(|i| items.get(i).cloned()).map(Box::new))
+            }
+            (Val::Map(map), Val::Str(key)) => Val::Opt(map.get(&key).cloned().map(Box::new)),
+            (a, b) => return Err(format!("cannot get {} from {}", b.type_name(), a.type_name())),
+        },
+        "and" => Val::Bool(arg().truthy()? && arg().truthy()?),
+        "or" => {
+            let (a, b) = (arg().truthy()?, arg().truthy()?);
+            Val::Bool(a || b)
+        }
+        "not" => Val::Bool(!arg().truthy()?),
+        "eq" => Val::Bool(compare("==", &arg(), &arg())?),
+        "neq" => Val::Bool(compare("!=", &arg(), &arg())?),
+        "gt" => Val::Bool(compare(">", &arg(), &arg())?),
+        "lt" => Val::Bool(compare("<", &arg(), &arg())?),
+        "min" | "max" => {
+            let (a, b) = (arg(), arg());
+            let a_first = compare("<=", &a, &b)? == (name == "min");
+            if a_first { a } else { b }
+        }
+        "abs" => match arg() {
+            Val::Int(n) => Val::Int(n.checked_abs().ok_or("attempt to abs with overflow")?),
+            other => Val::Float(num(&other)?.abs()),
+        },
+        "Some" => Val::Opt(Some(Box::new(arg()))),
+        "Ok" => Val::Res(Ok(Box::new(arg()))),
+        "Err" => Val::Res(Err(Box::new(arg()))),
+        "unwrap" => match arg() {
+            Val::Opt(Some(v)) | Val::Res(Ok(v)) => *v,
+            Val::Opt(None) => return Err("called `unwrap()` on a `None` value".to_string()),
+            Val::Res(Err(e)) => return Err(format!("called `unwrap()` on an `Err` value: {}", e.debug())),
+            other => other,
+        },
+        "unwrap_or" => match (arg(), arg()) {
+            (Val::Opt(Some(v)) | Val::Res(Ok(v)), _) => *v,
+            (Val::Opt(None) | Val::Res(Err(_)), default) => default,
+            (other, _) => other,
+        },
+        "is_some" => Val::Bool(matches!(arg(), Val::Opt(Some(_)))),
+        "is_none" => Val::Bool(matches!(arg(), Val::Opt(None))),
+        "is_ok" => Val::Bool(matches!(arg(), Val::Res(Ok(_)))),
+        "is_err" => Val::Bool(matches!(arg(), Val::Res(Err(_)))),
+        "contains" => match (arg(), arg()) {
+            (Val::Str(s), Val::Str(part)) => Val::Bool(s.contains(&part)),
+            (Val::List(items), item) => Val::Bool(items.contains(&item)),
+            (Val::Map(map), Val::Str(key)) => Val::Bool(map.contains_key(&key)),
+            (a, _) => return Err(format!("contains on {}", a.type_name())),
+        },
+        "to_string" => Val::Str(arg().display()),
+        "parse" => {
+            let text = arg().display();
+            let text = text.trim();
+            match text.parse::<i64>() {
+                Ok(n) => Val::Res(Ok(Box::new(Val::Int(n)))),
+                Err(_) => match text.parse::<f64>() {
+                    Ok(f) => Val::Res(Ok(Box::new(Val::Float(f)))),
+                    Err(e) => Val::Res(Err(Box::new(Val::Str(e.to_string())))),
+                },
+            }
+        }
+        // 函数形式的 println 和 Python 的 print 一样用空格拼接参数
+        "println" | "print" => {
+            let line: Vec<String> = args.map(|v| v.display()).collect();
+            stdout.push_str(&line.join(" "));
+            if name == "println" {
+                stdout.push('\n');
+            }
+            Val::Unit
+        }
+        _ => unreachable!(),
+    })
+}
+
+fn format_macro(args: Vec<Val>) -> Result<String, String> {
+    let mut args = args.into_iter();
+    let template = match args.next() {
+        Some(Val::Str(s)) => s,
+        Some(other) => return Err(format!("format string must be a literal, found {}", other.type_name())),
+        None => return Ok(String::new()),
+    };
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut spec = String::new();
+                for d in chars.by_ref() {
+                    if d == '}' {
+                        break;
+                    }
+                    spec.push(d);
+                }
+                let value = args.next().ok_or("missing format argument")?;
+                if spec.ends_with(":?") || spec.ends_with(":#?") {
+                    out.push_str(&value.debug());
+                } else {
+                    out.push_str(&value.display());
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    Ok(out)
+}
+
+// ==================== 解释执行 ====================
+enum Ctrl {
+    Error(String, Option<usize>),
+    Return(Val),
+    Break,
+    Continue,
+}
+
+impl From<String> for Ctrl {
+    fn from(message: String) -> Self {
+        Ctrl::Error(message, None)
+    }
+}
+
+impl From<&str> for Ctrl {
+    fn from(message: &str) -> Self {
+        Ctrl::Error(message.to_string(), None)
+    }
+}
+
+struct Interpreter {
+    scopes: Vec<HashMap<String, Val>>,
+    functions: HashMap<String, Rc<FnDef>>,
+    stdout: String,
+    steps: u64,
+    depth: usize,
+    nesting: usize,
+    // 执行器超时或取消时置位，下一步就停下
+    interrupt: Option<Arc<AtomicBool>>,
+}
+
+impl Interpreter {
+    fn lookup(&self, name: &str) -> Result<Val, Ctrl> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .ok_or_else(|| Ctrl::from(format!("cannot find value `{}` in this scope", name)))
+    }
+
+    fn set(&mut self, name: &str, value: Val) -> Result<(), Ctrl> {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(slot) = scope.get_mut(name) {
+                *slot = value;
+                return Ok(());
+            }
+        }
+        Err(format!("cannot find value `{}` in this scope", name).into())
+    }
+
+    fn tick(&mut self) -> Result<(), Ctrl> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return Err("step limit exceeded".into());
+        }
+        if let Some(interrupt) = &self.interrupt {
+            if interrupt.load(Ordering::Relaxed) {
+                return Err("interrupted".into());
+            }
+        }
+        Ok(())
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, Ctrl>) -> Result<T, Ctrl> {
+        if self.nesting >= MAX_EVAL_NESTING {
+            return Err("nesting too deep".into());
+        }
+        self.nesting += 1;
+        let result = f(self);
+        self.nesting -= 1;
+        result
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) -> Result<Val, Ctrl> {
+        self.scopes.push(HashMap::new());
+        let result = self.nested(|this| this.stmts(stmts));
+        self.scopes.pop();
+        result
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) -> Result<Val, Ctrl> {
+        // 先登记函数定义，允许先调用后定义
+        for stmt in stmts {
+            if let StmtKind::Fn(def) = &stmt.kind {
+                self.functions.insert(def.name.clone(), def.clone());
+            }
+        }
+        let mut last = Val::Unit;
+        for stmt in stmts {
+            last = self.stmt(stmt).map_err(|ctrl| match ctrl {
+                Ctrl::Error(message, None) => Ctrl::Error(message, Some(stmt.line)),
+                other => other,
+            })?;
+        }
+        Ok(last)
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Result<Val, Ctrl> {
+        self.tick()?;
+        match &stmt.kind {
+            StmtKind::Let(name, value) => {
+                let value = match value {
+                    Some(expr) => self.eval(expr)?,
+                    None => Val::Unit,
+                };
+                self.scopes.last_mut().unwrap().insert(name.clone(), value);
+            }
+            StmtKind::Assign(target, op, value) => {
+                let value = self.eval(value)?;
+                let value = match *op {
+                    "=" => value,
+                    op => arith(&op[..1], &self.eval(target)?, &value)?,
+                };
+                self.assign(target, value)?;
+            }
+            StmtKind::Expr(expr, semi) => {
+                let value = self.eval(expr)?;
+                return Ok(if *semi { Val::Unit } else { value });
+            }
+            StmtKind::Return(value) => {
+                let value = match value {
+                    Some(expr) => self.eval(expr)?,
+                    None => Val::Unit,
+                };
+                return Err(Ctrl::Return(value));
+            }
+            StmtKind::While(cond, body) => {
+                while self.eval(cond)?.truthy()? {
+                    match self.block(body) {
+                        Err(Ctrl::Break) => break,
+                        Err(Ctrl::Continue) | Ok(_) => {}
+                        Err(other) => return Err(other),
+                    }
+                    self.tick()?;
+                }
+            }
+            StmtKind::For(name, iter, body) => {
+                let items = match self.eval(iter)? {
+                    Val::List(items) => items,
+                    Val::Map(map) => map.into_iter().map(|(k, v)| Val::List(vec![Val::Str(k), v])).collect(),
+                    Val::Str(s) => s.chars().map(|c| Val::Str(c.to_string())).collect(),
+                    other => return Err(format!("{} is not iterable", other.type_name()).into()),
+                };
+                for item in items {
+                    self.scopes.push(HashMap::from([(name.clone(), item)]));
+                    let result = self.block(body);
+                    self.scopes.pop();
+                    match result {
+                        Err(Ctrl::Break) => break,
+                        Err(Ctrl::Continue) | Ok(_) => {}
+                        Err(other) => return Err(other),
+                    }
+                    self.tick()?;
+                }
+            }
+            StmtKind::Fn(_) => {}
+            StmtKind::Break => return Err(Ctrl::Break),
+            StmtKind::Continue => return Err(Ctrl::Continue),
+        }
+        Ok(Val::Unit)
+    }
+
+    fn assign(&mut self, target: &Expr, value: Val) -> Result<(), Ctrl> {
+        match target {
+            Expr::Var(name) => self.set(name, value),
+            Expr::Index(base, index) => {
+                let index = self.eval(index)?;
+                let updated = match (self.eval(base)?, index) {
+                    (Val::List(mut items), Val::Int(i)) => {
+                        let len = items.len();
+                        let slot = usize::try_from(i)
+                            .ok()
+                            .and_then(|i| items.get_mut(i))
+                            .ok_or_else(|| format!("index out of bounds: the len is {} but the index is {}", len, i))?;
+                        *slot = value;
+                        Val::List(items)
+                    }
+                    (Val::Map(mut map), Val::Str(key)) => {
+                        map.insert(key, value);
+                        Val::Map(map)
+                    }
+                    (base, index) => {
+                        return Err(format!("cannot index {} with {}", base.type_name(), index.type_name()).into())
+                    }
+                };
+                self.assign(base, updated)
+            }
+            _ => Err("invalid assignment target".into()),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Val, Ctrl> {
+        self.nested(|this| this.eval_expr(expr))
+    }
+
+    fn eval_expr(&mut self, expr: &Expr) -> Result<Val, Ctrl> {
+        match expr {
+            Expr::Lit(value) => Ok(value.clone()),
+            Expr::Var(name) => self.lookup(name),
+            Expr::Unary(op, inner) => match (*op, self.eval(inner)?) {
+                ("-", Val::Int(n)) => Ok(Val::Int(n.checked_neg().ok_or("attempt to negate with overflow")?)),
+                ("-", Val::Float(f)) => Ok(Val::Float(-f)),
+                ("!", Val::Bool(b)) => Ok(Val::Bool(!b)),
+                (op, other) => Err(format!("cannot apply unary operator `{}` to {}", op, other.type_name()).into()),
+            },
+            Expr::Binary("&&", a, b) => Ok(Val::Bool(self.eval(a)?.truthy()? && self.eval(b)?.truthy()?)),
+            Expr::Binary("||", a, b) => Ok(Val::Bool(self.eval(a)?.truthy()? || self.eval(b)?.truthy()?)),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (self.eval(a)?, self.eval(b)?);
+                match *op {
+                    "==" | "!=" | "<" | ">" | "<=" | ">=" => Ok(Val::Bool(compare(op, &a, &b)?)),
+                    _ => Ok(arith(op, &a, &b)?),
+                }
+            }
+            Expr::Range(start, end, inclusive) => match (self.eval(start)?, self.eval(end)?) {
+                (Val::Int(start), Val::Int(end)) => {
+                    let end = if *inclusive { end.saturating_add(1) } else { end };
+                    if end.saturating_sub(start) > MAX_RANGE {
+                        return Err("range too large".into());
+                    }
+                    Ok(Val::List((start..end).map(Val::Int).collect()))
+                }
+                _ => Err("range bounds must be integers".into()),
+            },
+            Expr::List(items) => Ok(Val::List(items.iter().map(|e| self.eval(e)).collect::<Result<_, _>>()?)),
+            Expr::Block(stmts) => self.block(stmts),
+            Expr::If(cond, then, otherwise) => {
+                if self.eval(cond)?.truthy()? {
+                    self.block(then)
+                } else if let Some(otherwise) = otherwise {
+                    self.eval(otherwise)
+                } else {
+                    Ok(Val::Unit)
+                }
+            }
+            Expr::Index(base, index) => match (self.eval(base)?, self.eval(index)?) {
+                (Val::List(items), Val::Int(i)) => usize::try_from(i)
+                    .ok()
+                    .and_then(|i| items.get(i).cloned())
+                    .ok_or_else(|| format!("index out of bounds: the len is {} but the index is {}", items.len(), i).into()),
+                // 缺失的 key 返回 None，和 serde_json 的 Index 行为一致
+                (Val::Map(map), Val::Str(key)) => Ok(map.get(&key).cloned().unwrap_or(Val::Opt(None))),
+                (Val::Str(s), Val::Int(i)) => usize::try_from(i)
+                    .ok()
+                    .and_then(|i| s.chars().nth(i))
+                    .map(|c| Val::Str(c.to_string()))
+                    .ok_or_else(|| "string index out of bounds".into()),
+                (base, index) => Err(format!("cannot index {} with {}", base.type_name(), index.type_name()).into()),
+            },
+            Expr::Field(base, name) => match (self.eval(base)?, name.parse::<usize>()) {
+                (Val::Map(map), _) => Ok(map.get(name).cloned().unwrap_or(Val::Opt(None))),
+                (Val::List(items), Ok(i)) => items.get(i).cloned().ok_or_else(|| "no such field".into()),
+                (other, _) => Err(format!("no field `{}` on {}", name, other.type_name()).into()),
+            },
+            Expr::Try(inner) => match self.eval(inner)? {
+                Val::Opt(Some(v)) | Val::Res(Ok(v)) => Ok(*v),
+                none @ Val::Opt(None) => Err(Ctrl::Return(none)),
+                err @ Val::Res(Err(_)) => Err(Ctrl::Return(err)),
+                other => Err(format!("the `?` operator cannot be applied to {}", other.type_name()).into()),
+            },
+            Expr::Cast(inner, ty) => {
+                let value = self.eval(inner)?;
+                match ty.as_str() {
+                    "f32" | "f64" => Ok(Val::Float(num(&value)?)),
+                    "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+                    | "usize" => match value {
+                        Val::Float(f) => Ok(Val::Int(f as i64)),
+                        Val::Bool(b) => Ok(Val::Int(b as i64)),
+                        other => Ok(Val::Int(num(&other)? as i64)),
+                    },
+                    _ => Err(format!("unsupported cast to {}", ty).into()),
+                }
+            }
+            Expr::Macro(name, args) => {
+                let args = args.iter().map(|e| self.eval(e)).collect::<Result<Vec<_>, _>>()?;
+                match name.as_str() {
+                    "vec" => Ok(Val::List(args)),
+                    "format" => Ok(Val::Str(format_macro(args)?)),
+                    "println" | "print" => {
+                        let text = format_macro(args)?;
+                        self.stdout.push_str(&text);
+                        if name == "println" {
+                            self.stdout.push('\n');
+                        }
+                        Ok(Val::Unit)
+                    }
+                    "panic" => Err(format!("panicked: {}", format_macro(args)?).into()),
+                    _ => Err(format!("cannot find macro `{}`", name).into()),
+                }
+            }
+            Expr::Call(name, args) => self.call(name, None, args),
+            Expr::Method(receiver, name, args) => self.call(method_builtin(name), Some(receiver), args),
+        }
+    }
+
+    fn call(&mut self, name: &str, receiver: Option<&Expr>, args: &[Expr]) -> Result<Val, Ctrl> {
+        let name = path_builtin(name);
+        let mut values = Vec::with_capacity(args.len() + 1);
+        if let Some(receiver) = receiver {
+            values.push(self.eval(receiver)?);
+        }
+        for arg in args {
+            values.push(self.eval(arg)?);
+        }
+
+        if let Some(def) = self.functions.get(name).cloned() {
+            return self.call_user(&def, values);
+        }
+
+        // vec_push(v, 1) / v.push(1) 会修改 v 本身
+        let place = receiver.or(args.first());
+        if MUTATING.contains(&name) {
+            if let Some(place @ (Expr::Var(_) | Expr::Index(..))) = place {
+                let result = call_builtin(name, values, &mut self.stdout)?;
+                return match (name, result) {
+                    ("vec_pop", Val::List(mut pair)) => {
+                        let popped = pair.pop().unwrap();
+                        self.assign(place, pair.pop().unwrap())?;
+                        Ok(popped)
+                    }
+                    (_, updated) => {
+                        self.assign(place, updated.clone())?;
+                        Ok(updated)
+                    }
+                };
+            }
+        }
+        match (name, call_builtin(name, values, &mut self.stdout)?) {
+            ("vec_pop", Val::List(mut pair)) => Ok(pair.pop().unwrap()),
+            (_, value) => Ok(value),
+        }
+    }
+
+    fn call_user(&mut self, def: &FnDef, args: Vec<Val>) -> Result<Val, Ctrl> {
+        if args.len() != def.params.len() {
+            return Err(format!(
+                "function {} takes {} argument(s) but {} were supplied",
+                def.name,
+                def.params.len(),
+                args.len()
+            )
+            .into());
+        }
+        if self.depth >= MAX_DEPTH {
+            return Err("recursion limit reached".into());
+        }
+        // 函数体看不到调用方的局部变量
+        let frame = HashMap::from_iter(def.params.iter().cloned().zip(args));
+        let saved = std::mem::replace(&mut self.scopes, vec![frame]);
+        self.depth += 1;
+        let result = self.stmts(&def.body);
+        self.depth -= 1;
+        self.scopes = saved;
+        match result {
+            Ok(value) | Err(Ctrl::Return(value)) => Ok(value),
+            Err(Ctrl::Break) | Err(Ctrl::Continue) => Err("`break` outside of a loop".into()),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Interprets `code` with `inputs` bound to the `input_N` map. The value of the
+/// last expression (or `return`) is the node result; `println!` output is collected.
+pub fn eval(code: &str, inputs: &Value) -> Result<(Value, String), String> {
+    let (result, stdout) = eval_interruptible(code, inputs, None)?;
+    result.map(|value| (value, stdout))
+}
+
+// 外层的 Err 是没跑起来（词法、语法错误）；跑起来以后出错也带着出错前打印的东西
+fn eval_interruptible(
+    code: &str,
+    inputs: &Value,
+    interrupt: Option<Arc<AtomicBool>>,
+) -> Result<(Result<Value, String>, String), String> {
+    on_stack(|| {
+        let program = Parser::new(tokenize(code)?).program()?;
+        let mut interpreter = Interpreter {
+            scopes: vec![HashMap::from([("inputs".to_string(), Val::from_value(inputs.clone()))])],
+            functions: HashMap::new(),
+            stdout: String::new(),
+            steps: 0,
+            depth: 0,
+            nesting: 0,
+            interrupt,
+        };
+        let result = match interpreter.stmts(&program) {
+            Ok(Val::Res(Err(e))) | Err(Ctrl::Return(Val::Res(Err(e)))) => Err(e.display()),
+            Ok(value) | Err(Ctrl::Return(value)) => Ok(value.to_value()),
+            Err(Ctrl::Error(message, Some(line))) => Err(format!("line {}: {}", line, message)),
+            Err(Ctrl::Error(message, None)) => Err(message),
+            Err(Ctrl::Break) | Err(Ctrl::Continue) => Err("`break` outside of a loop".to_string()),
+        };
+        Ok((result, interpreter.stdout))
+    })
+}
+
+// println! 的输出和 python / javascript 的 print 一样放在 envelope.stdout，留给运行记录
+fn envelope(code: &str, inputs: &Value, interrupt: Option<Arc<AtomicBool>>) -> Envelope {
+    match eval_interruptible(code, inputs, interrupt) {
+        Ok((result, stdout)) => {
+            let mut envelope = Envelope::from(result);
+            if !stdout.is_empty() {
+                envelope.stdout = Some(stdout);
+            }
+            envelope
+        }
+        Err(details) => Envelope::err(details),
+    }
+}
+
+// 调用方的线程栈多大不好说（tokio 的 blocking 线程只有 2 MiB），换到固定栈大小的线程上跑
+fn on_stack<T: Send>(f: impl FnOnce() -> Result<T, String> + Send) -> Result<T, String> {
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .map_err(|e| e.to_string())?
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// Parses `code` without running it; used by graph validation.
+pub fn check(code: &str) -> Result<(), String> {
+    on_stack(|| Parser::new(tokenize(code)?).program().map(|_| ()))
+}
+
+/// Same envelope as `python_exec`, for `codeType: "rustlite"` nodes.
+pub fn exec(code: &str, inputs: &Value) -> Envelope {
+    envelope(code, inputs, None)
+}
+
+/// Like `exec`, but stops with an error once `interrupt` is set.
+pub fn exec_interruptible(code: &str, inputs: &Value, interrupt: Arc<AtomicBool>) -> Envelope {
+    envelope(code, inputs, Some(interrupt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(code: &str) -> Result<Value, String> {
+        eval(code, &Value::Map(BTreeMap::new())).map(|(value, _)| value)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(run("1 + 2 * 3 - 4 / 2"), Ok(Value::Int(5)));
+        assert_eq!(run("(1 + 2) * 3 % 4"), Ok(Value::Int(1)));
+        assert_eq!(
+            run("-2 * 3 + 10 > 3 && !false || false"),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(run("10 - 2 - 3"), Ok(Value::Int(5)));
+        assert_eq!(run("2.0 * 3 as f64"), Ok(Value::Float(6.0)));
+    }
+
+    #[test]
+    fn control_flow() {
+        let code = "
+            let mut total = 0;
+            for i in 0..10 {
+                if i % 2 == 0 { continue; }
+                if i > 7 { break; }
+                total += i;
+            }
+            let mut n = 0;
+            while n < 3 { n += 1; }
+            total * 10 + n";
+        assert_eq!(run(code), Ok(Value::Int(163)));
+        let code = "
+            fn first_big(items: Vec<i64>) -> i64 {
+                for x in items { if x > 10 { return x; } }
+                -1
+            }
+            first_big(vec![3, 12, 40]) + first_big(vec![1])";
+        assert_eq!(run(code), Ok(Value::Int(11)));
+        assert_eq!(run("return 4; 5"), Ok(Value::Int(4)));
+        assert!(run("break;").unwrap_err().contains("outside of a loop"));
+    }
+
+    #[test]
+    fn integer_overflow() {
+        assert!(run("9223372036854775807 + 1")
+            .unwrap_err()
+            .contains("overflow"));
+        assert!(run("let x: i64 = 2; x.pow(70)")
+            .unwrap_err()
+            .contains("overflow"));
+        assert!(run("1 / 0").is_err());
+    }
+
+    #[test]
+    fn inputs_bindings() {
+        let inputs = Value::Map(BTreeMap::from([
+            ("input_0".to_string(), Value::Int(20)),
+            ("input_1".to_string(), Value::String("ab".to_string())),
+        ]));
+        let (value, _) = eval("inputs.input_0 + inputs.input_1.len() as i64", &inputs).unwrap();
+        assert_eq!(value, Value::Int(22));
+        assert_eq!(run("inputs.input_0.is_none()"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn recursion_cap() {
+        let code = "fn f(n: i64) -> i64 { if n == 0 { 0 } else { f(n - 1) + 1 } }";
+        assert_eq!(run(&format!("{} f(200)", code)), Ok(Value::Int(200)));
+        assert!(run(&format!("{} f(100000)", code))
+            .unwrap_err()
+            .contains("recursion limit reached"));
+    }
+
+    #[test]
+    fn nesting_cap() {
+        let deep = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert!(run(&deep).unwrap_err().contains("nesting too deep"));
+        assert!(check(&deep).unwrap_err().contains("nesting too deep"));
+        let blocks = format!("{}1{}", "{".repeat(100_000), "}".repeat(100_000));
+        assert!(run(&blocks).unwrap_err().contains("nesting too deep"));
+        assert!(run(&format!("{}1", "-".repeat(100_000)))
+            .unwrap_err()
+            .contains("nesting too deep"));
+    }
+
+    #[test]
+    fn interrupt_flag() {
+        let interrupt = Arc::new(AtomicBool::new(true));
+        let envelope = exec_interruptible("loop {}", &Value::Null, interrupt);
+        assert!(envelope.error);
+        assert_eq!(envelope.details.as_deref(), Some("line 1: interrupted"));
+    }
+
+    #[test]
+    fn println_output() {
+        let envelope = exec("println!(\"a {}\", 1); print!(\"b\"); 2", &Value::Null);
+        assert_eq!(envelope.result, Some(Value::Int(2)));
+        assert_eq!(envelope.stdout.as_deref(), Some("a 1\nb"));
+        let envelope = exec("println!(\"before\"); 1 / 0", &Value::Null);
+        assert!(envelope.error);
+        assert_eq!(envelope.stdout.as_deref(), Some("before\n"));
+        assert_eq!(exec("1", &Value::Null).stdout, None);
+    }
+}
@@ -0,0 +1,340 @@
+// Rust 节点：把 properties.fn 包装成临时二进制，编译后运行
+use crate::rust_cache::{self, RustCache};
+use crate::rust_diagnostics::{self, Diagnostic};
+use crate::value::{Envelope, Value};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+static RUSTC_VERSION: OnceCell<String> = OnceCell::const_new();
+
+// wrapper 自身需要 serde_json，节点没有声明时自动加上
+const DEFAULT_DEPENDENCY: &str = "serde_json = \"1\"";
+
+const MANIFEST: &str = r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+{dependencies}
+[workspace]
+"#;
+
+// 用户代码是 f 的函数体，inputs 就是 input_N 组成的 map
+// 大整数、二进制等按 value.rs 里的标签格式传进来
+const WRAPPER_HEAD: &str = "#[allow(unused_imports)]
+use serde_json::{json, Value};
+
+fn f(inputs: serde_json::Value) -> serde_json::Value {
+";
+
+const WRAPPER_TAIL: &str = "
+}
+
+fn main() {
+    use std::io::Read;
+    let mut raw = String::new();
+    std::io::stdin().read_to_string(&mut raw).expect(\"read inputs\");
+    let inputs = serde_json::from_str(&raw).unwrap_or(serde_json::Value::Null);
+    let result = f(inputs);
+    // 结果写到参数指定的文件里，用户代码可以随意 println!
+    let out = std::env::args().nth(1).expect(\"result path\");
+    std::fs::write(out, serde_json::to_vec(&result).expect(\"serialize result\")).expect(\"write result\");
+}
+";
+
+fn wrap(code: &str) -> String {
+    format!("{}{}{}", WRAPPER_HEAD, code, WRAPPER_TAIL)
+}
+
+struct Failure {
+    details: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl From<String> for Failure {
+    fn from(details: String) -> Self {
+        Self {
+            details,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    /// A `cargo vendor` directory or a local registry; when set, builds run offline against it.
+    pub vendor_dir: Option<PathBuf>,
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    current: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    pub fn load(path: PathBuf) -> Self {
+        let current = std::fs::read(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            current: Mutex::new(current),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.current.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, settings: Settings) -> Result<(), String> {
+        if let Some(dir) = &settings.vendor_dir {
+            if !dir.is_dir() {
+                return Err(format!("vendor directory not found: {}", dir.display()));
+            }
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let raw = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, raw).map_err(|e| e.to_string())?;
+        *self.current.lock().unwrap() = settings;
+        Ok(())
+    }
+}
+
+/// Turns the node's `properties.deps` lines (`regex = "1"`) into a `[dependencies]` body.
+fn dependencies(deps: &[String]) -> Result<String, String> {
+    let mut body = String::new();
+    let mut has_serde_json = false;
+    for line in deps.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        // 每行只能是 name = spec，不允许再开新的 [section]
+        let name = match line.split_once('=') {
+            Some((name, _)) if !line.starts_with('[') => name.trim(),
+            _ => return Err(format!("invalid dependency declaration: {}", line)),
+        };
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("invalid crate name in: {}", line));
+        }
+        has_serde_json |= name == "serde_json";
+        body.push_str(line);
+        body.push('\n');
+    }
+    if !has_serde_json {
+        body.insert_str(0, &format!("{}\n", DEFAULT_DEPENDENCY));
+    }
+    Ok(body)
+}
+
+// 把 crates-io 替换为本地 vendor 目录（或 local registry），离线解析依赖
+fn vendor_config(vendor_dir: &Path) -> String {
+    let kind = if vendor_dir.join("index").is_dir() {
+        "local-registry"
+    } else {
+        "directory"
+    };
+    let path = vendor_dir
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    format!(
+        "[source.crates-io]\nreplace-with = \"hp-vendor\"\n\n[source.hp-vendor]\n{} = \"{}\"\n\n[net]\noffline = true\n",
+        kind, path
+    )
+}
+
+fn binary_name(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Compiles `code` as the body of `fn f(inputs) -> Value`, runs it with `inputs`
+/// on stdin and returns the same envelope as `python_exec`.
+/// Unchanged nodes reuse the binary stored in `cache`.
+pub async fn exec(
+    code: &str,
+    deps: &[String],
+    inputs: &Value,
+    cache: &RustCache,
+    settings: &Settings,
+) -> Envelope {
+    let binary = match artifact(code, deps, cache, settings).await {
+        Ok(binary) => binary,
+        Err(failure) => {
+            let mut envelope = Envelope::err(failure.details);
+            envelope.diagnostics = failure.diagnostics;
+            return envelope;
+        }
+    };
+    run(&binary, inputs).await.unwrap_or_else(Envelope::err)
+}
+
+async fn rustc_version() -> Result<String, String> {
+    RUSTC_VERSION
+        .get_or_try_init(|| async {
+            let output = Command::new("rustc")
+                .arg("-V")
+                .output()
+                .await
+                .map_err(|e| format!("failed to start rustc: {}", e))?;
+            // 失败的结果不进 RUSTC_VERSION，下次再试；空版本号会让缓存键对不上工具链
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !output.status.success() || version.is_empty() {
+                return Err(format!(
+                    "rustc -V failed ({}): {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            Ok(version)
+        })
+        .await
+        .cloned()
+}
+
+async fn artifact(
+    code: &str,
+    deps: &[String],
+    cache: &RustCache,
+    settings: &Settings,
+) -> Result<PathBuf, Failure> {
+    let rustc = rustc_version().await?;
+    let dependencies = dependencies(deps)?;
+    let vendor = settings.vendor_dir.as_deref().map(vendor_config);
+    // vendor 目录不同，解析出的版本也可能不同
+    let declared = match &vendor {
+        Some(config) => format!("{}\n{}", dependencies, config),
+        None => dependencies.clone(),
+    };
+    // 包装模板改了，同样的代码也要重新编译
+    let key = rust_cache::key(&wrap(code), &rustc, &declared);
+    let lock = cache.lock(&key);
+    let _guard = lock.lock().await;
+    if let Some(binary) = cache.lookup(&key) {
+        return Ok(binary);
+    }
+
+    let project = cache.build_dir(&key);
+    let name = format!("hp_node_{}", &key[..16]);
+    let built = build(
+        &project,
+        &cache.target_dir(),
+        &name,
+        code,
+        &dependencies,
+        vendor.as_deref(),
+    )
+    .await;
+    let _ = tokio::fs::remove_dir_all(&project).await;
+    Ok(cache.store(&key, &built?, code, &rustc, &declared)?)
+}
+
+async fn build(
+    project: &Path,
+    target_dir: &Path,
+    name: &str,
+    code: &str,
+    dependencies: &str,
+    vendor: Option<&str>,
+) -> Result<PathBuf, Failure> {
+    tokio::fs::create_dir_all(project.join("src"))
+        .await
+        .map_err(|e| format!("create build dir failed: {}", e))?;
+    if let Some(config) = vendor {
+        tokio::fs::create_dir_all(project.join(".cargo"))
+            .await
+            .map_err(|e| e.to_string())?;
+        tokio::fs::write(project.join(".cargo").join("config.toml"), config)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let manifest = MANIFEST
+        .replace("{name}", name)
+        .replace("{dependencies}", dependencies);
+    tokio::fs::write(project.join("Cargo.toml"), manifest)
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::fs::write(project.join("src").join("main.rs"), wrap(code))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 所有节点共用一个 target 目录，serde_json 只需编译一次
+    let build = Command::new("cargo")
+        .args(["build", "--release", "--quiet", "--message-format=json"])
+        .current_dir(project)
+        .env("CARGO_TARGET_DIR", target_dir)
+        // 运行被取消或超时的时候 cargo 也一起结束
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("failed to start cargo: {}", e))?;
+    if !build.status.success() {
+        let stdout = String::from_utf8_lossy(&build.stdout);
+        let (diagnostics, rendered) =
+            rust_diagnostics::parse(&stdout, WRAPPER_HEAD.lines().count(), code);
+        // 依赖解析失败等 cargo 自身的错误只在 stderr 里
+        let details = if rendered.is_empty() {
+            String::from_utf8_lossy(&build.stderr).to_string()
+        } else {
+            rendered
+        };
+        return Err(Failure {
+            details,
+            diagnostics,
+        });
+    }
+    Ok(target_dir.join("release").join(binary_name(name)))
+}
+
+// 节点程序的 stdout / stderr 不管成败都放进 envelope，留给运行记录
+async fn run(binary: &Path, inputs: &Value) -> Result<Envelope, String> {
+    let result_path = std::env::temp_dir().join(format!(
+        "hp_node_result_{}_{}.json",
+        std::process::id(),
+        RUN_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    let mut child = Command::new(binary)
+        .arg(&result_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to start node binary: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let payload = serde_json::to_vec(inputs).map_err(|e| e.to_string())?;
+        stdin.write_all(&payload).await.map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
+    let raw = tokio::fs::read(&result_path).await;
+    let _ = tokio::fs::remove_file(&result_path).await;
+    let text =
+        |bytes: &[u8]| (!bytes.is_empty()).then(|| String::from_utf8_lossy(bytes).into_owned());
+    let result = if !output.status.success() {
+        Err(text(&output.stderr).unwrap_or_else(|| format!("node exited with {}", output.status)))
+    } else {
+        raw.map_err(|e| format!("node produced no result: {}", e))
+            .and_then(|raw| serde_json::from_slice(&raw).map_err(|e| e.to_string()))
+    };
+    let mut envelope = Envelope::from(result);
+    envelope.stdout = text(&output.stdout);
+    envelope.stderr = text(&output.stderr);
+    Ok(envelope)
+}
@@ -0,0 +1,499 @@
+// 把工作流编译成一个独立脚本（bash 或 Python），不装 hp 也能跑
+// 节点按拓扑顺序内联 properties.fn，input_N 的接线和执行器一致：
+// 每个节点产出一个按输出口排列的值列表，下游从 origin_slot 取值
+// 跑不了的节点（rust / rustlite / wasm / 没有 codeType）记进 unsupported，脚本里留一个会报错的桩
+use crate::graph::{Graph, Node, MODE_NEVER};
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Target {
+    Bash,
+    Python,
+}
+
+impl Target {
+    pub fn parse(name: &str) -> Result<Target, String> {
+        match name {
+            "bash" | "sh" => Ok(Target::Bash),
+            "python" | "py" => Ok(Target::Python),
+            other => Err(format!(
+                "unknown script target `{}`, expected bash or python",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Unsupported {
+    pub node_id: u64,
+    pub title: String,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct Compiled {
+    pub script: String,
+    /// Nodes the script cannot run; each one is a stub that fails when reached.
+    pub unsupported: Vec<Unsupported>,
+}
+
+// JavaScript 节点交给 node 跑，labelMarkedForOutputs 的处理和 derivedNodes.js 一致
+// argv: 代码、输出口名字（JSON 数组）；stdin: inputs
+const JS_RUNNER: &str = r#"const code = process.argv[1];
+const names = JSON.parse(process.argv[2]);
+const inputs = JSON.parse(require('fs').readFileSync(0, 'utf8'));
+console.log = console.error;
+Promise.resolve(new Function('inputs', code)(inputs)).then((result) => {
+    let slots = names.map(() => result);
+    if (result && typeof result === 'object' && result.hasOwnProperty('labelMarkedForOutputs')) {
+        if (result.labelMarkedForOutputs === 'outputs') {
+            slots = names.map((name, i) => result.hasOwnProperty(`output_${i}`) ? result[`output_${i}`] : result[name]);
+        } else if (result.error === true) {
+            console.error(result.details ?? result.detail);
+            process.exit(1);
+        } else {
+            slots = names.map(() => result.result);
+        }
+    }
+    process.stdout.write(JSON.stringify(slots.map((v) => v === undefined ? null : v)));
+});"#;
+
+// bash 里的 Python 节点：argv: 代码、输出口个数；stdin: inputs
+const PY_RUNNER: &str = r#"import json, sys
+code, n = sys.argv[1], int(sys.argv[2])
+scope = {}
+exec('def f(inputs):\n' + '\n'.join('    ' + line for line in code.split('\n')), scope)
+inputs = json.load(sys.stdin)
+out, sys.stdout = sys.stdout, sys.stderr
+result = scope['f'](inputs)
+out.write(json.dumps([result] * n, default=str))"#;
+
+const BASH_PRELUDE: &str = r#"set -euo pipefail
+command -v jq >/dev/null || { echo "this script needs jq" >&2; exit 1; }
+
+ARGS=("$@")
+# 没连线的输入口：命令行上的 key=value，值能按 JSON 解析就按 JSON
+binding() {
+    local arg
+    for arg in ${ARGS[@]+"${ARGS[@]}"}; do
+        if [ "${arg%%=*}" = "$1" ]; then
+            local value="${arg#*=}"
+            # 不能用 jq -e：false / null 也是合法的 JSON
+            if [ -n "$value" ] && jq . >/dev/null 2>&1 <<<"$value"; then printf '%s' "$value"; else jq -n --arg v "$value" '$v'; fi
+            return
+        fi
+    done
+    echo null
+}
+
+# $1 = 输出口个数；把 stdin 上的一个值复制到每个输出口
+spread() {
+    jq -c --argjson n "$1" '. as $v | [range($n) | $v]'
+}
+
+# shell 节点的结果和 hp 一样是 {code, stdout, stderr}
+run_shell() {
+    local out err code=0
+    out=$(mktemp) err=$(mktemp)
+    bash -c "$1" >"$out" 2>"$err" || code=$?
+    jq -n --rawfile stdout "$out" --rawfile stderr "$err" --argjson code "$code" \
+        '{code: $code, stdout: $stdout, stderr: $stderr}'
+    rm -f "$out" "$err"
+}
+"#;
+
+const PYTHON_PRELUDE: &str = r#"import json
+import subprocess
+import sys
+
+# 没连线的输入口：命令行上的 key=value，值能按 JSON 解析就按 JSON
+BINDINGS = {}
+for _arg in sys.argv[1:]:
+    _key, _, _value = _arg.partition('=')
+    try:
+        BINDINGS[_key] = json.loads(_value)
+    except ValueError:
+        BINDINGS[_key] = _value
+
+
+# shell 节点的结果和 hp 一样是 {code, stdout, stderr}
+def run_shell(command, inputs):
+    p = subprocess.run(['bash', '-c', command], input=json.dumps(inputs, default=str).encode(), capture_output=True)
+    return {
+        'code': p.returncode,
+        'stdout': p.stdout.decode(errors='replace'),
+        'stderr': p.stderr.decode(errors='replace'),
+    }
+
+
+def run_javascript(code, names, inputs):
+    p = subprocess.run(['node', '-e', JS_RUNNER, code, json.dumps(names)],
+                       input=json.dumps(inputs, default=str).encode(), capture_output=True)
+    sys.stderr.write(p.stderr.decode(errors='replace'))
+    if p.returncode != 0:
+        raise RuntimeError('javascript node failed')
+    return json.loads(p.stdout)
+"#;
+
+fn sh_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn json_str(text: &str) -> String {
+    serde_json::to_string(text).unwrap()
+}
+
+// 能写成三引号就写成三引号，代码保持原样可读
+fn py_str(text: &str) -> String {
+    if !text.contains("'''") && !text.contains('\\') && !text.ends_with('\'') {
+        format!("'''{}'''", text)
+    } else {
+        json_str(text)
+    }
+}
+
+// heredoc 的结束标记不能和代码里的某一行撞上
+fn heredoc(tag: &str, text: &str) -> String {
+    let mut tag = tag.to_string();
+    while text.lines().any(|line| line.trim() == tag) {
+        tag.push('_');
+    }
+    format!("\"$(cat <<'{tag}'\n{text}\n{tag}\n)\"")
+}
+
+fn unsupported(node: &Node) -> Option<String> {
+    match node.code_type() {
+        Some("python" | "javascript" | "sh") => None,
+        Some(kind) => Some(format!("codeType `{}` cannot run outside hp", kind)),
+        None => Some(format!("node type `{}` has no codeType", node.kind)),
+    }
+}
+
+fn output_names(node: &Node) -> Vec<&str> {
+    node.outputs()
+        .iter()
+        .map(|output| output.name.as_str())
+        .collect()
+}
+
+fn bash(graph: &Graph, order: &[&Node], report: &[Unsupported]) -> String {
+    let mut script = String::from(
+        "#!/usr/bin/env bash\n# generated by `hp compile`; nodes run in topological order\n",
+    );
+    script.push_str("# usage: flow.sh [key=value]...   values for unconnected input ports\n");
+    script.push_str(BASH_PRELUDE);
+    script.push_str(&format!(
+        "\nJS_RUNNER={}\nPY_RUNNER={}\n",
+        sh_quote(JS_RUNNER),
+        sh_quote(PY_RUNNER)
+    ));
+
+    let mut summary = Vec::new();
+    for node in order {
+        let id = node.id;
+        let n = node.outputs().len();
+        script.push_str(&format!("\n# node {}: {}\n", id, node.title()));
+        // input_N：连线的取上游对应输出口，没连线的取命令行
+        let mut args = Vec::new();
+        for (i, (link, input)) in graph
+            .incoming(node)
+            .into_iter()
+            .zip(node.inputs())
+            .enumerate()
+        {
+            let value = match link {
+                Some(link) => format!(
+                    "\"$(jq -c '.[{}]' <<<\"$out_{}\")\"",
+                    link.origin_slot, link.origin_id
+                ),
+                None => format!("\"$(binding {})\"", sh_quote(&input.name)),
+            };
+            args.push(format!("--argjson input_{} {}", i, value));
+        }
+        script.push_str(&format!(
+            "inputs_{}=$(jq -n {} '$ARGS.named')\n",
+            id,
+            args.join(" ")
+        ));
+
+        let call = if node.mode == MODE_NEVER {
+            format!("jq -n 'null' | spread {}", n)
+        } else if let Some(reason) = report.iter().find(|u| u.node_id == id).map(|u| &u.reason) {
+            format!(
+                "{{ echo {} >&2; exit 1; }}",
+                sh_quote(&format!("node {}: {}", id, reason))
+            )
+        } else {
+            let code = heredoc(&format!("HP_NODE_{}", id), node.code());
+            match node.code_type() {
+                Some("sh") => format!("run_shell {} <<<\"$inputs_{}\" | spread {}", code, id, n),
+                Some("python") => format!(
+                    "python3 -c \"$PY_RUNNER\" {} {} <<<\"$inputs_{}\"",
+                    code, n, id
+                ),
+                _ => format!(
+                    "node -e \"$JS_RUNNER\" {} {} <<<\"$inputs_{}\"",
+                    code,
+                    sh_quote(&serde_json::to_string(&output_names(node)).unwrap()),
+                    id
+                ),
+            }
+        };
+        script.push_str(&format!("out_{}=$({})\n", id, call));
+        summary.push(format!(
+            "jq -n --argjson outputs \"$out_{}\" --arg title {} '{{id: {}, title: $title, outputs: $outputs}}'",
+            id,
+            sh_quote(node.title()),
+            id
+        ));
+    }
+    script.push_str(&format!(
+        "\n{{\n    {}\n}} | jq -s '{{nodes: .}}'\n",
+        summary.join("\n    ")
+    ));
+    script
+}
+
+fn python(graph: &Graph, order: &[&Node], report: &[Unsupported]) -> String {
+    let mut script = String::from(
+        "#!/usr/bin/env python3\n# generated by `hp compile`; nodes run in topological order\n",
+    );
+    script
+        .push_str("# usage: python3 flow.py [key=value]...   values for unconnected input ports\n");
+    script.push_str(PYTHON_PRELUDE);
+    script.push_str(&format!("\nJS_RUNNER = {}\n", py_str(JS_RUNNER)));
+
+    let mut body = Vec::new();
+    for node in order {
+        let id = node.id;
+        let n = node.outputs().len();
+        script.push_str(&format!("\n\n# node {}: {}\n", id, node.title()));
+        let reason = report.iter().find(|u| u.node_id == id).map(|u| &u.reason);
+        match (node.code_type(), reason) {
+            (_, Some(reason)) => script.push_str(&format!(
+                "def node_{}(inputs):\n    raise NotImplementedError({})\n",
+                id,
+                json_str(&format!("node {}: {}", id, reason))
+            )),
+            (Some("python"), None) => {
+                script.push_str(&format!("def node_{}(inputs):\n", id));
+                let code = node.code();
+                if code.trim().is_empty() {
+                    script.push_str("    pass\n");
+                }
+                for line in code.lines().filter(|_| !code.trim().is_empty()) {
+                    script.push_str(&format!("    {}\n", line).replace("    \n", "\n"));
+                }
+                script.push_str(&format!(
+                    "\n\ndef run_node_{}(inputs):\n    return [node_{}(inputs)] * {}\n",
+                    id, id, n
+                ));
+            }
+            (Some("sh"), None) => script.push_str(&format!(
+                "def run_node_{}(inputs):\n    return [run_shell({}, inputs)] * {}\n",
+                id,
+                py_str(node.code()),
+                n
+            )),
+            _ => script.push_str(&format!(
+                "def run_node_{}(inputs):\n    return run_javascript({}, {}, inputs)\n",
+                id,
+                py_str(node.code()),
+                serde_json::to_string(&output_names(node)).unwrap()
+            )),
+        }
+        if reason.is_some() {
+            script.push_str(&format!(
+                "\n\ndef run_node_{}(inputs):\n    return [node_{}(inputs)] * {}\n",
+                id, id, n
+            ));
+        }
+
+        let inputs: Vec<String> = graph
+            .incoming(node)
+            .into_iter()
+            .zip(node.inputs())
+            .enumerate()
+            .map(|(i, (link, input))| match link {
+                Some(link) => format!(
+                    "'input_{}': out[{}][{}]",
+                    i, link.origin_id, link.origin_slot
+                ),
+                None => format!("'input_{}': BINDINGS.get({})", i, json_str(&input.name)),
+            })
+            .collect();
+        let call = if node.mode == MODE_NEVER {
+            format!("[None] * {}", n)
+        } else {
+            format!("run_node_{}({{{}}})", id, inputs.join(", "))
+        };
+        body.push(format!("    out[{}] = {}", id, call));
+        body.push(format!(
+            "    nodes.append({{'id': {}, 'title': {}, 'outputs': out[{}]}})",
+            id,
+            json_str(node.title()),
+            id
+        ));
+    }
+    // 节点里的 print 和 bash 版一样走 stderr，stdout 只留给最后的结果
+    script.push_str("\n\ndef main():\n    out = {}\n    nodes = []\n    stdout, sys.stdout = sys.stdout, sys.stderr\n");
+    for line in body {
+        script.push_str(&line);
+        script.push('\n');
+    }
+    script.push_str("    print(json.dumps({'nodes': nodes}, default=str, indent=2), file=stdout)\n\n\nif __name__ == '__main__':\n    main()\n");
+    script
+}
+
+/// Compiles `graph` into a standalone script for `target`.
+pub fn compile(graph: &Graph, target: Target) -> Result<Compiled, String> {
+    let order: Vec<&Node> = graph
+        .topo_order()?
+        .into_iter()
+        .map(|id| graph.node(id).unwrap())
+        .collect();
+    let report: Vec<Unsupported> = order
+        .iter()
+        .filter(|node| node.mode != MODE_NEVER)
+        .filter_map(|node| {
+            unsupported(node).map(|reason| Unsupported {
+                node_id: node.id,
+                title: node.title().to_string(),
+                reason,
+            })
+        })
+        .collect();
+    let script = match target {
+        Target::Bash => bash(graph, &order, &report),
+        Target::Python => python(graph, &order, &report),
+    };
+    Ok(Compiled {
+        script,
+        unsupported: report,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::process::Command;
+
+    fn graph(nodes: Value, links: Value) -> Graph {
+        Graph::from_json(json!({ "nodes": nodes, "links": links })).unwrap()
+    }
+
+    fn node(id: u64, code_type: &str, code: &str, inputs: Value) -> Value {
+        json!({
+            "id": id,
+            "type": "code/Node",
+            "inputs": inputs,
+            "outputs": [{"name": "out", "type": "*", "links": null}],
+            "properties": {"codeType": code_type, "fn": code}
+        })
+    }
+
+    // 一个节点，输入口 flag 没连线，原样返回 inputs
+    fn echo(code_type: &str, code: &str) -> Graph {
+        graph(
+            json!([node(
+                1,
+                code_type,
+                code,
+                json!([{"name": "flag", "type": "*", "link": null}])
+            )]),
+            json!([]),
+        )
+    }
+
+    fn available(program: &str) -> bool {
+        Command::new(program).arg("--version").output().is_ok()
+    }
+
+    // 跑生成的脚本，返回节点 1 的第一个输出
+    fn run(program: &str, script: &str, args: &[&str]) -> Value {
+        // bash -c 的下一个参数是 $0，python3 -c 的不是
+        let name = (program == "bash").then_some(program);
+        let output = Command::new(program)
+            .arg("-c")
+            .arg(script)
+            .args(name)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+        report["nodes"][0]["outputs"][0].clone()
+    }
+
+    #[test]
+    fn targets() {
+        assert!(Target::parse("sh") == Ok(Target::Bash));
+        assert!(Target::parse("py") == Ok(Target::Python));
+        assert!(Target::parse("ruby").is_err());
+    }
+
+    #[test]
+    fn unsupported_nodes_become_stubs() {
+        let graph = graph(json!([node(1, "rustlite", "1", json!([]))]), json!([]));
+        for target in [Target::Bash, Target::Python] {
+            let compiled = compile(&graph, target).unwrap();
+            assert_eq!(compiled.unsupported.len(), 1);
+            assert_eq!(compiled.unsupported[0].node_id, 1);
+            assert!(compiled
+                .script
+                .contains("node 1: codeType `rustlite` cannot run outside hp"));
+        }
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(py_str("a'b"), "'''a'b'''");
+        assert_eq!(py_str("a\\b"), "\"a\\\\b\"");
+        assert_eq!(
+            heredoc("T", "x\nT\n  T_ "),
+            "\"$(cat <<'T__'\nx\nT\n  T_ \nT__\n)\""
+        );
+    }
+
+    #[test]
+    fn bash_bindings() {
+        if !available("bash") || !available("jq") {
+            return;
+        }
+        let script = compile(&echo("sh", "cat"), Target::Bash).unwrap().script;
+        let flag = |args: &[&str]| {
+            let result = run("bash", &script, args);
+            let inputs: Value = serde_json::from_str(result["stdout"].as_str().unwrap()).unwrap();
+            inputs["input_0"].clone()
+        };
+        assert_eq!(flag(&["flag=false"]), json!(false));
+        assert_eq!(flag(&["flag=null"]), json!(null));
+        assert_eq!(flag(&["flag=[1,2]"]), json!([1, 2]));
+        assert_eq!(flag(&["flag=hello"]), json!("hello"));
+        assert_eq!(flag(&["flag="]), json!(""));
+        assert_eq!(flag(&[]), json!(null));
+    }
+
+    #[test]
+    fn python_bindings() {
+        if !available("python3") {
+            return;
+        }
+        let script = compile(
+            &echo("python", "print('ignored')\nreturn inputs['input_0']"),
+            Target::Python,
+        )
+        .unwrap()
+        .script;
+        assert_eq!(run("python3", &script, &["flag=false"]), json!(false));
+        assert_eq!(run("python3", &script, &["flag=hello"]), json!("hello"));
+        assert_eq!(run("python3", &script, &[]), json!(null));
+    }
+}
@@ -0,0 +1,146 @@
+// 脱离 webview 运行 Python / JavaScript 节点：起一个解释器子进程
+// 运行器和 GUI 用的是同一份代码（src-python/main.py，src-js/main.js）
+use crate::value::{Envelope, Value};
+use serde::Deserialize;
+use serde_json::json;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const PYTHON_MAIN: &str = include_str!("../src-python/main.py");
+const JS_MAIN: &str = include_str!("../src-js/main.js");
+
+// 用户代码里的 print 先攒起来放进 envelope.stdout，进程的 stdout 只留给 envelope
+const PYTHON_DRIVER: &str = r#"
+import io as _io, json as _json, sys as _sys
+_request = _json.load(_sys.stdin)
+_stdout, _sys.stdout = _sys.stdout, _io.StringIO()
+_reply = python_exec(_request['code'], _request['inputs'])
+if _sys.stdout.getvalue():
+    _reply['stdout'] = _sys.stdout.getvalue()
+_stdout.write(_json.dumps(_reply, allow_nan=False))
+"#;
+
+// 语法检查：只编译不执行，一次检查一批节点，输出 [{id, line?, message}]
+const PYTHON_CHECK: &str = r#"
+import ast, json, sys
+found = []
+for item in json.load(sys.stdin):
+    body = '\n'.join('    ' + line for line in item['code'].split('\n'))
+    try:
+        ast.parse('def f(inputs):\n' + body + '\n')
+    except SyntaxError as e:
+        found.append({'id': item['id'], 'line': max((e.lineno or 2) - 1, 1), 'message': e.msg})
+print(json.dumps(found))
+"#;
+
+const JS_CHECK: &str = r#"
+const items = JSON.parse(require('fs').readFileSync(0, 'utf8'));
+const found = [];
+for (const item of items) {
+    try {
+        new Function('inputs', item.code);
+    } catch (err) {
+        found.push({ id: item.id, message: String(err.message) });
+    }
+}
+process.stdout.write(JSON.stringify(found));
+"#;
+
+#[derive(Deserialize)]
+pub struct SyntaxError {
+    pub id: u64,
+    #[serde(default)]
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+fn python_program() -> &'static str {
+    if cfg!(windows) {
+        "python"
+    } else {
+        "python3"
+    }
+}
+
+async fn run(program: &str, script: &str, code: &str, inputs: &Value) -> Result<Envelope, String> {
+    let mut child = Command::new(program)
+        .arg(if program == "node" { "-e" } else { "-c" })
+        .arg(script)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to start {}: {}", program, e))?;
+
+    let request = serde_json::to_vec(&json!({ "code": code, "inputs": inputs }))
+        .map_err(|e| e.to_string())?;
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(&request).await.map_err(|e| e.to_string())?;
+    drop(stdin);
+
+    let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
+    let mut envelope: Envelope = serde_json::from_slice(&output.stdout).map_err(|_| {
+        format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+    })?;
+    // print / console.log 在 envelope.stdout 里，写到 stderr 的留在这，都留给运行记录
+    if !output.stderr.is_empty() {
+        envelope.stderr = Some(String::from_utf8_lossy(&output.stderr).into_owned());
+    }
+    Ok(envelope)
+}
+
+/// Runs a `codeType: "python"` node with the system `python3`.
+pub async fn python(code: &str, inputs: &Value) -> Envelope {
+    let script = format!("{}\n{}", PYTHON_MAIN, PYTHON_DRIVER);
+    run(python_program(), &script, code, inputs)
+        .await
+        .unwrap_or_else(Envelope::err)
+}
+
+/// Runs a `codeType: "javascript"` node with `node`.
+pub async fn javascript(code: &str, inputs: &Value) -> Envelope {
+    run("node", JS_MAIN, code, inputs)
+        .await
+        .unwrap_or_else(Envelope::err)
+}
+
+async fn check(program: &str, script: &str, items: &[(u64, &str)]) -> Option<Vec<SyntaxError>> {
+    let mut child = Command::new(program)
+        .arg(if program == "node" { "-e" } else { "-c" })
+        .arg(script)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .ok()?;
+    let items: Vec<_> = items
+        .iter()
+        .map(|(id, code)| json!({ "id": id, "code": code }))
+        .collect();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin
+        .write_all(&serde_json::to_vec(&items).ok()?)
+        .await
+        .ok()?;
+    drop(stdin);
+    let output = child.wait_with_output().await.ok()?;
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// Syntax errors of Python node bodies, or `None` when `python3` is unavailable.
+pub async fn check_python(items: &[(u64, &str)]) -> Option<Vec<SyntaxError>> {
+    check(python_program(), PYTHON_CHECK, items).await
+}
+
+/// Syntax errors of JavaScript node bodies, or `None` when `node` is unavailable.
+pub async fn check_javascript(items: &[(u64, &str)]) -> Option<Vec<SyntaxError>> {
+    check("node", JS_CHECK, items).await
+}
@@ -0,0 +1,137 @@
+// shell 节点：用系统 shell 跑 properties.fn，inputs 以 JSON 写入 stdin
+// one2oneTerminal（codeType "one2one"）：每个输入本身是一条命令，结果给同下标的输出
+use crate::graph::Node;
+use crate::value::{Envelope, Value};
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+fn shell(command: &str) -> Command {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("zsh");
+        cmd.arg("-c");
+        cmd
+    } else {
+        let mut cmd = Command::new("bash");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command);
+    cmd
+}
+
+// 取消或超时时 child 被 drop，kill_on_drop 只杀得到 shell 本身；
+// unix 上让 shell 自成进程组，没正常结束就把整组杀掉，免得 sleep 之类的孙进程留下来
+struct KillGroupOnDrop(Option<u32>);
+
+impl Drop for KillGroupOnDrop {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            let _ = std::process::Command::new("kill")
+                .args(["-KILL", "--", &format!("-{}", pid)])
+                .stderr(Stdio::null())
+                .status();
+        }
+    }
+}
+
+// 输出不是 UTF-8 时保留原始字节
+fn stream(bytes: Vec<u8>) -> Value {
+    match String::from_utf8(bytes) {
+        Ok(text) => Value::String(text),
+        Err(e) => Value::Bytes(e.into_bytes()),
+    }
+}
+
+async fn run(command: &str, inputs: &Value) -> Result<Value, String> {
+    let mut command = shell(command);
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to start shell: {}", e))?;
+    let mut group = KillGroupOnDrop(child.id());
+
+    let payload = serde_json::to_vec(inputs).map_err(|e| e.to_string())?;
+    let mut stdin = child.stdin.take().unwrap();
+    // 命令不读 stdin 时写入会 broken pipe，忽略
+    let _ = stdin.write_all(&payload).await;
+    drop(stdin);
+
+    let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
+    group.0 = None;
+    let mut result = BTreeMap::new();
+    result.insert(
+        "code".to_string(),
+        output
+            .status
+            .code()
+            .map(|c| Value::Int(c as i64))
+            .unwrap_or_default(),
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = output.status.signal() {
+            result.insert("signal".to_string(), Value::Int(signal as i64));
+        }
+    }
+    result.insert("stdout".to_string(), stream(output.stdout));
+    result.insert("stderr".to_string(), stream(output.stderr));
+    Ok(Value::Map(result))
+}
+
+/// Runs `command` in the platform shell and returns `{code, signal?, stdout, stderr}`.
+/// A non-zero exit code is still a result; only a failure to start is an error.
+pub async fn exec(command: &str, inputs: &Value) -> Envelope {
+    run(command, inputs).await.into()
+}
+
+/// Runs a `shell/one2oneTerminal` node: every string `input_N` is a command whose
+/// result goes to output N (other inputs give `null`). When `properties.fn` is
+/// set it runs afterwards, and its result goes to every output.
+pub async fn one_to_one(node: &Node, inputs: &Value) -> Envelope {
+    let outputs = node.outputs().len();
+    let mut results = BTreeMap::new();
+    for slot in 0..node.inputs().len() {
+        let command = match inputs {
+            Value::Map(map) => map.get(&format!("input_{}", slot)),
+            _ => None,
+        };
+        let result = match command {
+            Some(Value::String(command)) if !command.is_empty() => {
+                match run(command, &Value::Null).await {
+                    Ok(result) => result,
+                    Err(e) => return Envelope::err(e),
+                }
+            }
+            _ => Value::Null,
+        };
+        if slot < outputs {
+            results.insert(format!("output_{}", slot), result);
+        }
+    }
+    if !node.code().is_empty() {
+        let result = match run(node.code(), &Value::Null).await {
+            Ok(result) => result,
+            Err(e) => return Envelope::err(e),
+        };
+        for slot in 0..outputs {
+            results.insert(format!("output_{}", slot), result.clone());
+        }
+    }
+    Envelope {
+        label_marked_for_outputs: "outputs".to_string(),
+        ..Envelope::ok(Value::Map(results))
+    }
+}
@@ -0,0 +1,111 @@
+// 子图节点：graph/subgraph 把一整张图存在节点的 subgraph 字段里（和 LiteGraph 自带的 Subgraph 一样），
+// 里面的 graph/input / graph/output 按 properties.name 对应外面节点的同名输入 / 输出口
+// 执行时递归跑里面的图：外面的输入按端口名喂给 graph/input，graph/output 收到的值就是外面的输出
+use crate::executor::{self, Runtime, Status};
+use crate::graph::{Graph, Node};
+use crate::value::{Envelope, Value};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+
+fn input_0(inputs: &Value) -> Value {
+    match inputs {
+        Value::Map(map) => map.get("input_0").cloned().unwrap_or_default(),
+        _ => Value::Null,
+    }
+}
+
+/// Runs the inner graph of a `graph/subgraph` node. Output slots whose
+/// `graph/output` node was skipped stay inactive, so branches inside the
+/// subgraph carry on outside it.
+pub fn exec<'a>(
+    node: &'a Node,
+    inputs: &'a Value,
+    runtime: &'a Runtime,
+) -> Pin<Box<dyn Future<Output = Envelope> + Send + 'a>> {
+    // 执行器的任务经 dispatch 又会调到这里，返回装箱的 Future 断开 async fn 的类型环
+    Box::pin(run(node, inputs, runtime))
+}
+
+async fn run(node: &Node, inputs: &Value, runtime: &Runtime) -> Envelope {
+    match node.subgraph() {
+        Some(Ok(graph)) => run_graph(&graph, node, inputs, runtime, "subgraph").await,
+        Some(Err(e)) => Envelope::err(format!("invalid subgraph: {}", e)),
+        None => Envelope::err("subgraph node has no subgraph"),
+    }
+}
+
+/// Runs `graph` for `node`: the node's inputs feed the `graph/input` nodes of
+/// the same name, and each output takes what the `graph/output` of that name
+/// received. `what` names the graph in errors.
+pub async fn run_graph(
+    graph: &Graph,
+    node: &Node,
+    inputs: &Value,
+    runtime: &Runtime,
+    what: &str,
+) -> Envelope {
+    let bindings: BTreeMap<String, Value> = match inputs {
+        Value::Map(map) => node
+            .inputs()
+            .iter()
+            .enumerate()
+            .map(|(i, input)| {
+                let value = map.get(&format!("input_{}", i)).cloned();
+                (input.name.clone(), value.unwrap_or_default())
+            })
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+    let mut results = match run_bound(graph, &bindings, runtime, what).await {
+        Ok(results) => results,
+        Err(e) => return Envelope::err(e),
+    };
+    let outputs = node
+        .outputs()
+        .iter()
+        .enumerate()
+        .filter_map(|(slot, output)| {
+            let value = results.remove(&output.name)?;
+            Some((format!("output_{}", slot), value))
+        })
+        .collect();
+    Envelope {
+        label_marked_for_outputs: "outputs".to_string(),
+        ..Envelope::ok(Value::Map(outputs))
+    }
+}
+
+/// Runs `graph` with `bindings` fed to its `graph/input` nodes by name, and
+/// returns what each `graph/output` received, by name. Outputs whose node was
+/// skipped are left out.
+pub async fn run_bound(
+    graph: &Graph,
+    bindings: &BTreeMap<String, Value>,
+    runtime: &Runtime,
+    what: &str,
+) -> Result<BTreeMap<String, Value>, String> {
+    // 里面的节点不发事件、不停断点，取消和外面共用
+    let inner = Runtime {
+        events: None,
+        debugger: None,
+        step: false,
+        ..runtime.clone()
+    };
+    let report = executor::run(graph, &inner, bindings).await?;
+    if !report.ok {
+        return Err(format!("in {}: {}", what, report.error.unwrap_or_default()));
+    }
+    let mut results = BTreeMap::new();
+    for boundary in &graph.nodes {
+        let name = match boundary.port_name() {
+            Some(name) if boundary.code_type() == Some("output") => name,
+            _ => continue,
+        };
+        let run = report.nodes.iter().find(|run| run.id == boundary.id);
+        if let Some(run) = run.filter(|run| run.status != Status::Skipped) {
+            results.insert(name.to_string(), input_0(&run.inputs));
+        }
+    }
+    Ok(results)
+}
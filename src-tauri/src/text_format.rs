@@ -0,0 +1,518 @@
+// 适合 code review 的文本格式（YAML）
+// 节点代码写成块标量，连线写成 "节点id.端口名"，和 LiteGraph JSON 互转不丢信息：
+//   links:
+//   - id: 1
+//     from: 3.output
+//     to: 1.input
+// 端口上的 link / links 能从 links 推出来时省略，推不出来（文件本身不一致）时原样保留
+// 子图节点里的 subgraph 也按同样的格式写
+use crate::graph::{Graph, Input, Link, Node, Output};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    version: f64,
+    #[serde(default)]
+    last_node_id: u64,
+    #[serde(default)]
+    last_link_id: u64,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    extra: Map<String, Value>,
+    #[serde(default)]
+    nodes: Vec<TextNode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    links: Vec<TextLink>,
+    #[serde(flatten)]
+    rest: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TextNode {
+    id: u64,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    mode: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inputs: Option<Vec<Port>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    outputs: Option<Vec<Port>>,
+    #[serde(flatten)]
+    rest: Map<String, Value>,
+    // 代码放在最后，读起来最顺
+    #[serde(default)]
+    properties: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Port {
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: Value,
+    /// Only written when it differs from what `links` implies.
+    #[serde(
+        default,
+        deserialize_with = "explicit",
+        skip_serializing_if = "Option::is_none"
+    )]
+    link: Option<Value>,
+    #[serde(
+        default,
+        deserialize_with = "explicit",
+        skip_serializing_if = "Option::is_none"
+    )]
+    links: Option<Value>,
+    #[serde(flatten)]
+    rest: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TextLink {
+    id: u64,
+    from: String,
+    to: String,
+    #[serde(rename = "type", default)]
+    kind: Value,
+}
+
+fn is_zero(mode: &u8) -> bool {
+    *mode == 0
+}
+
+// 区分 "没写" 和 "写了 null"
+fn explicit<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+// 端口名在节点里唯一时写 "3.output"，否则写下标 "3[0]"；
+// 名字里带 [ ] . 的（比如 data[0]）写成 "3.data[0]" 就读不回来了，也写下标
+fn endpoint(node_id: u64, slot: usize, names: Option<Vec<&str>>) -> String {
+    if let Some(names) = names {
+        if let Some(name) = names.get(slot) {
+            if !name.is_empty()
+                && !name.contains(['[', ']', '.'])
+                && names.iter().filter(|n| *n == name).count() == 1
+            {
+                return format!("{}.{}", node_id, name);
+            }
+        }
+    }
+    format!("{}[{}]", node_id, slot)
+}
+
+fn parse_endpoint<'a>(
+    text: &str,
+    names: impl Fn(u64) -> Option<&'a Vec<String>>,
+) -> Result<(u64, usize), String> {
+    let bad = || {
+        format!(
+            "bad link endpoint `{}`, expected `node.port` or `node[slot]`",
+            text
+        )
+    };
+    if let Some((node, slot)) = text.strip_suffix(']').and_then(|t| t.split_once('[')) {
+        return Ok((
+            node.parse().map_err(|_| bad())?,
+            slot.parse().map_err(|_| bad())?,
+        ));
+    }
+    let (node, port) = text.split_once('.').ok_or_else(bad)?;
+    let node: u64 = node.parse().map_err(|_| bad())?;
+    let slot = names(node)
+        .and_then(|names| names.iter().position(|name| name == port))
+        .ok_or_else(|| {
+            format!(
+                "link endpoint `{}`: node {} has no port `{}`",
+                text, node, port
+            )
+        })?;
+    Ok((node, slot))
+}
+
+fn expected_input_link(links: &[Link], node: u64, slot: usize) -> Option<u64> {
+    links
+        .iter()
+        .find(|link| link.target_id == node && link.target_slot == slot)
+        .map(|link| link.id)
+}
+
+fn expected_output_links(links: &[Link], node: u64, slot: usize) -> Option<Vec<u64>> {
+    let ids: Vec<u64> = links
+        .iter()
+        .filter(|link| link.origin_id == node && link.origin_slot == slot)
+        .map(|link| link.id)
+        .collect();
+    (!ids.is_empty()).then_some(ids)
+}
+
+// 子图节点的 subgraph 也是一张图，同样写成文本格式，里面的连线才好 review
+fn subgraph_to_text(rest: &Map<String, Value>) -> Map<String, Value> {
+    let mut rest = rest.clone();
+    if let Some(raw) = rest.get_mut("subgraph") {
+        if let Ok(inner) = Graph::from_json(raw.clone()) {
+            if let Ok(doc) = serde_json::to_value(to_document(&inner)) {
+                *raw = doc;
+            }
+        }
+    }
+    rest
+}
+
+// 老文件里子图原样存成 LiteGraph JSON（links 是数组），解析不成 Document，保持不动
+fn subgraph_from_text(mut rest: Map<String, Value>) -> Result<Map<String, Value>, String> {
+    if let Some(raw) = rest.get_mut("subgraph") {
+        if let Ok(doc) = serde_json::from_value::<Document>(raw.clone()) {
+            *raw = serde_json::to_value(from_document(doc)?).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(rest)
+}
+
+fn to_document(graph: &Graph) -> Document {
+    let input_names = |id: u64| {
+        graph
+            .node(id)
+            .map(|node| node.inputs().iter().map(|p| p.name.as_str()).collect())
+    };
+    let output_names = |id: u64| {
+        graph
+            .node(id)
+            .map(|node| node.outputs().iter().map(|p| p.name.as_str()).collect())
+    };
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|node| TextNode {
+            id: node.id,
+            kind: node.kind.clone(),
+            title: node.title.clone(),
+            mode: node.mode,
+            inputs: node.inputs.as_ref().map(|inputs| {
+                inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, input)| Port {
+                        name: input.name.clone(),
+                        kind: input.kind.clone(),
+                        link: (input.link != expected_input_link(&graph.links, node.id, slot))
+                            .then(|| serde_json::json!(input.link)),
+                        links: None,
+                        rest: input.rest.clone(),
+                    })
+                    .collect()
+            }),
+            outputs: node.outputs.as_ref().map(|outputs| {
+                outputs
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, output)| Port {
+                        name: output.name.clone(),
+                        kind: output.kind.clone(),
+                        link: None,
+                        links: (output.links != expected_output_links(&graph.links, node.id, slot))
+                            .then(|| serde_json::json!(output.links)),
+                        rest: output.rest.clone(),
+                    })
+                    .collect()
+            }),
+            rest: subgraph_to_text(&node.rest),
+            properties: node.properties.clone(),
+        })
+        .collect();
+    let links = graph
+        .links
+        .iter()
+        .map(|link| TextLink {
+            id: link.id,
+            from: endpoint(
+                link.origin_id,
+                link.origin_slot,
+                output_names(link.origin_id),
+            ),
+            to: endpoint(
+                link.target_id,
+                link.target_slot,
+                input_names(link.target_id),
+            ),
+            kind: link.kind.clone(),
+        })
+        .collect();
+    Document {
+        version: graph.version,
+        last_node_id: graph.last_node_id,
+        last_link_id: graph.last_link_id,
+        extra: graph.extra.clone(),
+        nodes,
+        links,
+        rest: graph.rest.clone(),
+    }
+}
+
+fn from_document(doc: Document) -> Result<Graph, String> {
+    let port_names = |ports: &Option<Vec<Port>>| -> Vec<String> {
+        ports
+            .iter()
+            .flatten()
+            .map(|port| port.name.clone())
+            .collect()
+    };
+    let names: HashMap<u64, (Vec<String>, Vec<String>)> = doc
+        .nodes
+        .iter()
+        .map(|node| {
+            (
+                node.id,
+                (port_names(&node.inputs), port_names(&node.outputs)),
+            )
+        })
+        .collect();
+    let mut links = Vec::with_capacity(doc.links.len());
+    for link in &doc.links {
+        let (origin_id, origin_slot) =
+            parse_endpoint(&link.from, |id| names.get(&id).map(|(_, outputs)| outputs))?;
+        let (target_id, target_slot) =
+            parse_endpoint(&link.to, |id| names.get(&id).map(|(inputs, _)| inputs))?;
+        links.push(Link {
+            id: link.id,
+            origin_id,
+            origin_slot,
+            target_id,
+            target_slot,
+            kind: link.kind.clone(),
+        });
+    }
+    let mut nodes = Vec::with_capacity(doc.nodes.len());
+    for node in doc.nodes {
+        let id = node.id;
+        let inputs = match node.inputs {
+            Some(ports) => Some(
+                ports
+                    .into_iter()
+                    .enumerate()
+                    .map(|(slot, port)| {
+                        let link = match port.link {
+                            Some(link) => {
+                                serde_json::from_value(link).map_err(|e| e.to_string())?
+                            }
+                            None => expected_input_link(&links, id, slot),
+                        };
+                        Ok(Input {
+                            name: port.name,
+                            kind: port.kind,
+                            link,
+                            rest: port.rest,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, String>>()?,
+            ),
+            None => None,
+        };
+        let outputs = match node.outputs {
+            Some(ports) => Some(
+                ports
+                    .into_iter()
+                    .enumerate()
+                    .map(|(slot, port)| {
+                        let links = match port.links {
+                            Some(ids) => serde_json::from_value(ids).map_err(|e| e.to_string())?,
+                            None => expected_output_links(&links, id, slot),
+                        };
+                        Ok(Output {
+                            name: port.name,
+                            kind: port.kind,
+                            links,
+                            rest: port.rest,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, String>>()?,
+            ),
+            None => None,
+        };
+        nodes.push(Node {
+            id,
+            kind: node.kind,
+            mode: node.mode,
+            title: node.title,
+            inputs,
+            outputs,
+            properties: node.properties,
+            rest: subgraph_from_text(node.rest)?,
+        });
+    }
+    Ok(Graph {
+        last_node_id: doc.last_node_id,
+        last_link_id: doc.last_link_id,
+        nodes,
+        links,
+        extra: doc.extra,
+        version: doc.version,
+        rest: doc.rest,
+    })
+}
+
+/// Renders a graph in the YAML text format.
+pub fn to_text(graph: &Graph) -> Result<String, String> {
+    serde_yaml::to_string(&to_document(graph)).map_err(|e| e.to_string())
+}
+
+/// Reads the YAML text format back into LiteGraph JSON (not yet migrated).
+pub fn from_text(text: &str) -> Result<Value, String> {
+    let doc: Document =
+        serde_yaml::from_str(text).map_err(|e| format!("invalid workflow text: {}", e))?;
+    serde_json::to_value(from_document(doc)?).map_err(|e| e.to_string())
+}
+
+pub fn is_text_path(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("yaml" | "yml")
+    )
+}
+
+/// Reads a workflow file as LiteGraph JSON, choosing the format by extension.
+pub fn read(path: &Path) -> Result<Value, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    if is_text_path(path) {
+        from_text(&text)
+    } else {
+        serde_json::from_str(&text)
+            .map_err(|e| format!("invalid JSON in {}: {}", path.display(), e))
+    }
+}
+
+/// Writes a graph in the format chosen by the extension of `path`.
+pub fn write(path: &Path, graph: &Graph) -> Result<(), String> {
+    let text = if is_text_path(path) {
+        to_text(graph)?
+    } else {
+        serde_json::to_string_pretty(graph).map_err(|e| e.to_string())?
+    };
+    std::fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // JSON -> YAML -> JSON 之后和原来一模一样
+    fn round_trip(raw: Value) -> String {
+        let graph = Graph::from_json(raw.clone()).unwrap();
+        let text = to_text(&graph).unwrap();
+        let back = from_text(&text).unwrap();
+        assert_eq!(
+            serde_json::to_value(Graph::from_json(back).unwrap()).unwrap(),
+            raw
+        );
+        text
+    }
+
+    fn node(id: u64, inputs: Value, outputs: Value) -> Value {
+        json!({
+            "id": id,
+            "type": "code/Python",
+            "mode": 0,
+            "inputs": inputs,
+            "outputs": outputs,
+            "properties": {"codeType": "python", "fn": "return inputs"}
+        })
+    }
+
+    fn graph(nodes: Value, links: Value) -> Value {
+        json!({
+            "last_node_id": 2,
+            "last_link_id": 2,
+            "nodes": nodes,
+            "links": links,
+            "extra": {"hpFormat": 1},
+            "version": 0.4
+        })
+    }
+
+    #[test]
+    fn duplicate_port_names() {
+        let text = round_trip(graph(
+            json!([
+                node(
+                    1,
+                    json!([]),
+                    json!([
+                        {"name": "out", "type": "*", "links": [1]},
+                        {"name": "out", "type": "*", "links": [2]}
+                    ])
+                ),
+                node(
+                    2,
+                    json!([
+                        {"name": "in", "type": "*", "link": 1},
+                        {"name": "in", "type": "*", "link": 2}
+                    ]),
+                    json!([])
+                ),
+            ]),
+            json!([[1, 1, 0, 2, 0, "*"], [2, 1, 1, 2, 1, "*"]]),
+        ));
+        assert!(text.contains("from: 1[1]"), "{}", text);
+    }
+
+    #[test]
+    fn bracketed_port_names() {
+        let text = round_trip(graph(
+            json!([
+                node(
+                    1,
+                    json!([]),
+                    json!([{"name": "data[0]", "type": "*", "links": [1]}])
+                ),
+                node(
+                    2,
+                    json!([{"name": "a.b", "type": "*", "link": 1}]),
+                    json!([])
+                ),
+            ]),
+            json!([[1, 1, 0, 2, 0, "*"]]),
+        ));
+        assert!(text.contains("from: 1[0]"), "{}", text);
+        assert!(text.contains("to: 2[0]"), "{}", text);
+    }
+
+    #[test]
+    fn nested_subgraphs() {
+        let inner = graph(
+            json!([
+                node(
+                    1,
+                    json!([]),
+                    json!([{"name": "x", "type": "*", "links": [1]}])
+                ),
+                node(2, json!([{"name": "y", "type": "*", "link": 1}]), json!([])),
+            ]),
+            json!([[1, 1, 0, 2, 0, "*"]]),
+        );
+        let mut middle = graph(json!([node(1, json!([]), json!([]))]), json!([]));
+        middle["nodes"][0]["subgraph"] = inner;
+        let mut outer = graph(json!([node(1, json!([]), json!([]))]), json!([]));
+        outer["nodes"][0]["subgraph"] = middle;
+        let text = round_trip(outer.clone());
+        // 最里面那层的连线也写成 from / to
+        assert!(text.contains("from: 1.x"), "{}", text);
+        assert!(text.contains("to: 2.y"), "{}", text);
+
+        // 老文件里子图原样存成 LiteGraph JSON，照样读得回来
+        let mut doc: Value =
+            serde_yaml::from_str(&to_text(&Graph::from_json(outer.clone()).unwrap()).unwrap())
+                .unwrap();
+        doc["nodes"][0]["subgraph"] = outer["nodes"][0]["subgraph"].clone();
+        let back = from_text(&serde_yaml::to_string(&doc).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(Graph::from_json(back).unwrap()).unwrap(),
+            outer
+        );
+    }
+}
@@ -0,0 +1,784 @@
+// 图的静态检查：连线完整性、端口下标、端口类型、codeType、明显的语法错误
+// 手改或合并过的 JSON 在打开 / 运行之前先过一遍
+use crate::executor::CODE_TYPES;
+use crate::graph::{Graph, Node};
+use crate::{body, loops, retry, rust_interp, script_node};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// "error" or "warning".
+    pub severity: String,
+    /// Stable kebab-case identifier, e.g. `missing-node`.
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_id: Option<u64>,
+    /// Line in `properties.fn`, for syntax errors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+}
+
+impl Finding {
+    fn error(code: &str, message: String) -> Self {
+        Finding {
+            severity: "error".to_string(),
+            code: code.to_string(),
+            message,
+            node_id: None,
+            link_id: None,
+            line: None,
+        }
+    }
+
+    fn warning(code: &str, message: String) -> Self {
+        Finding {
+            severity: "warning".to_string(),
+            ..Finding::error(code, message)
+        }
+    }
+
+    fn node(mut self, id: u64) -> Self {
+        self.node_id = Some(id);
+        self
+    }
+
+    fn link(mut self, id: u64) -> Self {
+        self.link_id = Some(id);
+        self
+    }
+}
+
+// LiteGraph 的端口类型：""、"*"、0 都是通配，"a,b" 表示多选
+fn port_type(kind: &Value) -> Option<String> {
+    match kind {
+        Value::String(s) if s.is_empty() || s == "*" => None,
+        Value::String(s) => Some(s.to_lowercase()),
+        Value::Number(n) if n.as_i64() == Some(0) => None,
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn label(kind: &Value) -> String {
+    kind.as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| kind.to_string())
+}
+
+// 和 LiteGraph.isValidConnection 相同的规则
+fn compatible(output: &Value, input: &Value) -> bool {
+    let (output, input) = match (port_type(output), port_type(input)) {
+        (Some(output), Some(input)) => (output, input),
+        _ => return true,
+    };
+    output
+        .split(',')
+        .any(|a| input.split(',').any(|b| a.trim() == b.trim()))
+}
+
+fn check_links(graph: &Graph, findings: &mut Vec<Finding>) {
+    let mut seen = HashSet::new();
+    for node in &graph.nodes {
+        if !seen.insert(node.id) {
+            findings.push(
+                Finding::error(
+                    "duplicate-node",
+                    format!("node id {} is used twice", node.id),
+                )
+                .node(node.id),
+            );
+        }
+    }
+    let mut seen = HashSet::new();
+    for link in &graph.links {
+        if !seen.insert(link.id) {
+            findings.push(
+                Finding::error(
+                    "duplicate-link",
+                    format!("link id {} is used twice", link.id),
+                )
+                .link(link.id),
+            );
+        }
+        let origin = graph.node(link.origin_id);
+        let target = graph.node(link.target_id);
+        for (end, id, node) in [
+            ("origin", link.origin_id, origin),
+            ("target", link.target_id, target),
+        ] {
+            if node.is_none() {
+                findings.push(
+                    Finding::error(
+                        "missing-node",
+                        format!("link {} {} node {} does not exist", link.id, end, id),
+                    )
+                    .link(link.id),
+                );
+            }
+        }
+        let output = origin.and_then(|node| node.outputs().get(link.origin_slot));
+        let input = target.and_then(|node| node.inputs().get(link.target_slot));
+        if let (Some(node), None) = (origin, output) {
+            findings.push(
+                Finding::error(
+                    "bad-slot",
+                    format!(
+                        "link {} leaves output slot {} of a node with {} outputs",
+                        link.id,
+                        link.origin_slot,
+                        node.outputs().len()
+                    ),
+                )
+                .node(node.id)
+                .link(link.id),
+            );
+        }
+        if let (Some(node), None) = (target, input) {
+            findings.push(
+                Finding::error(
+                    "bad-slot",
+                    format!(
+                        "link {} enters input slot {} of a node with {} inputs",
+                        link.id,
+                        link.target_slot,
+                        node.inputs().len()
+                    ),
+                )
+                .node(node.id)
+                .link(link.id),
+            );
+        }
+        if let (Some(output), Some(input)) = (output, input) {
+            if !compatible(&output.kind, &input.kind) {
+                findings.push(
+                    Finding::error(
+                        "type-mismatch",
+                        format!(
+                            "link {} connects `{}` output to `{}` input",
+                            link.id,
+                            label(&output.kind),
+                            label(&input.kind)
+                        ),
+                    )
+                    .node(link.target_id)
+                    .link(link.id),
+                );
+            }
+            if input.link != Some(link.id) {
+                findings.push(
+                    Finding::warning(
+                        "link-mismatch",
+                        format!(
+                            "input `{}` does not point back to link {}",
+                            input.name, link.id
+                        ),
+                    )
+                    .node(link.target_id)
+                    .link(link.id),
+                );
+            }
+            if !output.links.iter().flatten().any(|&id| id == link.id) {
+                findings.push(
+                    Finding::warning(
+                        "link-mismatch",
+                        format!("output `{}` does not list link {}", output.name, link.id),
+                    )
+                    .node(link.origin_id)
+                    .link(link.id),
+                );
+            }
+        }
+    }
+    // 节点端口上引用了不存在的 link
+    for node in &graph.nodes {
+        for input in node.inputs() {
+            if let Some(id) = input.link.filter(|&id| graph.link(id).is_none()) {
+                findings.push(
+                    Finding::error(
+                        "dangling-link",
+                        format!("input `{}` refers to missing link {}", input.name, id),
+                    )
+                    .node(node.id)
+                    .link(id),
+                );
+            }
+        }
+    }
+}
+
+fn check_node(node: &Node, findings: &mut Vec<Finding>) {
+    match node.code_type() {
+        None => findings.push(
+            Finding::error(
+                "missing-code-type",
+                format!("node `{}` has no properties.codeType", node.title()),
+            )
+            .node(node.id),
+        ),
+        Some(kind) if !CODE_TYPES.contains(&kind) => findings.push(
+            Finding::error(
+                "unknown-code-type",
+                format!("node `{}` has unknown codeType `{}`", node.title(), kind),
+            )
+            .node(node.id),
+        ),
+        // If / Switch 的 fn 可以不写，one2oneTerminal 的命令在输入口上，子图、Call 和边界节点没有代码
+        Some("call") if node.workflow_path().is_none() => findings.push(
+            Finding::error(
+                "missing-path",
+                format!("Call node `{}` has no properties.path", node.title()),
+            )
+            .node(node.id),
+        ),
+        // Map / While / Repeat 的 fn 是循环体的代码
+        Some("map" | "while" | "repeat") => match node.body_type() {
+            None => findings.push(
+                Finding::error(
+                    "bad-body",
+                    format!("node `{}` has no properties.bodyType", node.title()),
+                )
+                .node(node.id),
+            ),
+            Some("subgraph") => {}
+            Some(kind) if !body::is_body_type(kind) => findings.push(
+                Finding::error(
+                    "bad-body",
+                    format!(
+                        "node `{}` cannot run codeType `{}` as its body",
+                        node.title(),
+                        kind
+                    ),
+                )
+                .node(node.id),
+            ),
+            Some(_) if node.code().trim().is_empty() => findings.push(
+                Finding::warning("empty-code", format!("node `{}` has no code", node.title()))
+                    .node(node.id),
+            ),
+            Some(_) => {}
+        },
+        Some("if" | "switch" | "one2one" | "subgraph" | "call" | "input" | "output") => {}
+        Some(_) if node.code().trim().is_empty() => findings.push(
+            Finding::warning("empty-code", format!("node `{}` has no code", node.title()))
+                .node(node.id),
+        ),
+        Some(_) => {}
+    }
+    let outputs = node.outputs().len();
+    match node.code_type() {
+        Some("if") if outputs != 2 => findings.push(
+            Finding::error(
+                "bad-branch",
+                format!(
+                    "If node `{}` needs two outputs (then, else), has {}",
+                    node.title(),
+                    outputs
+                ),
+            )
+            .node(node.id),
+        ),
+        Some("switch") if outputs == 0 => findings.push(
+            Finding::warning(
+                "bad-branch",
+                format!("Switch node `{}` has no outputs", node.title()),
+            )
+            .node(node.id),
+        ),
+        _ => {}
+    }
+    if let Err(message) = node.timeout() {
+        findings.push(Finding::error("bad-timeout", message).node(node.id));
+    }
+    if let Err(message) = retry::policy(node) {
+        findings.push(Finding::error("bad-retry", message).node(node.id));
+    }
+    if loops::is_loop(node) {
+        check_loop(node, findings);
+    }
+}
+
+fn check_loop(node: &Node, findings: &mut Vec<Finding>) {
+    let limit = match loops::max_iterations(node) {
+        Ok(limit) => limit,
+        Err(message) => {
+            findings.push(Finding::error("bad-loop", message).node(node.id));
+            return;
+        }
+    };
+    if node.code_type() != Some("repeat") {
+        return;
+    }
+    match loops::count(node) {
+        Ok(count) if count > limit => findings.push(
+            Finding::error(
+                "bad-loop",
+                format!("count {} is over maxIterations {}", count, limit),
+            )
+            .node(node.id),
+        ),
+        Ok(_) => {}
+        Err(message) => findings.push(Finding::error("bad-loop", message).node(node.id)),
+    }
+}
+
+// rustlite 用自己的解析器，Python / JavaScript 交给本机解释器，没装就跳过
+async fn check_syntax(graph: &Graph, findings: &mut Vec<Finding>) {
+    let mut python = Vec::new();
+    let mut javascript = Vec::new();
+    for node in &graph.nodes {
+        if node.code_type() == Some("while") && !loops::condition(node).trim().is_empty() {
+            if let Err(message) = rust_interp::check(loops::condition(node)) {
+                findings.push(
+                    Finding::error("syntax", format!("condition: {}", message)).node(node.id),
+                );
+            }
+        }
+        if node.code().trim().is_empty() {
+            continue;
+        }
+        // 循环体的 fn 按 bodyType 检查
+        let kind = match node.code_type() {
+            Some("map" | "while" | "repeat") => {
+                node.body_type().filter(|kind| body::is_body_type(kind))
+            }
+            kind => kind,
+        };
+        match kind {
+            Some("python") => python.push((node.id, node.code())),
+            Some("javascript") => javascript.push((node.id, node.code())),
+            // If / Switch 的条件也是 rustlite
+            Some("rustlite" | "if" | "switch") => {
+                if let Err(message) = rust_interp::check(node.code()) {
+                    findings.push(Finding::error("syntax", message).node(node.id));
+                }
+            }
+            _ => {}
+        }
+    }
+    let (python, javascript) = tokio::join!(
+        async {
+            if python.is_empty() {
+                None
+            } else {
+                script_node::check_python(&python).await
+            }
+        },
+        async {
+            if javascript.is_empty() {
+                None
+            } else {
+                script_node::check_javascript(&javascript).await
+            }
+        },
+    );
+    for error in python.into_iter().chain(javascript).flatten() {
+        let mut finding = Finding::error("syntax", error.message).node(error.id);
+        finding.line = error.line;
+        findings.push(finding);
+    }
+}
+
+// 子图里的图递归检查一遍，问题挂在外面的子图节点上
+// ports 是 (input / output, 端口名, 每个端口是否都要有同名的边界节点)
+async fn check_subgraph(
+    node: &Node,
+    ports: [(&str, Vec<&str>, bool); 2],
+    findings: &mut Vec<Finding>,
+) {
+    let raw = match node.rest.get("subgraph") {
+        Some(raw) => raw.clone(),
+        None => {
+            findings.push(
+                Finding::error(
+                    "missing-subgraph",
+                    format!("subgraph node `{}` has no subgraph", node.title()),
+                )
+                .node(node.id),
+            );
+            return;
+        }
+    };
+    let inner = Graph::from_json(raw.clone());
+    for finding in Box::pin(validate(raw)).await {
+        findings.push(Finding {
+            message: format!("in subgraph `{}`: {}", node.title(), finding.message),
+            node_id: Some(node.id),
+            link_id: None,
+            line: None,
+            ..finding
+        });
+    }
+    let inner = match inner {
+        Ok(inner) => inner,
+        Err(_) => return,
+    };
+    for (kind, ports, required) in ports {
+        let boundary: Vec<&str> = inner
+            .nodes
+            .iter()
+            .filter(|inner| inner.code_type() == Some(kind))
+            .filter_map(Node::port_name)
+            .collect();
+        for port in ports.iter().filter(|_| required) {
+            if !boundary.contains(port) {
+                findings.push(
+                    Finding::error(
+                        "subgraph-port",
+                        format!(
+                            "subgraph `{}` has no graph/{} named `{}`",
+                            node.title(),
+                            kind,
+                            port
+                        ),
+                    )
+                    .node(node.id),
+                );
+            }
+        }
+        for name in boundary {
+            if !ports.contains(&name) {
+                findings.push(
+                    Finding::warning(
+                        "subgraph-port",
+                        format!(
+                            "graph/{} `{}` in subgraph `{}` matches no port",
+                            kind,
+                            name,
+                            node.title()
+                        ),
+                    )
+                    .node(node.id),
+                );
+            }
+        }
+    }
+}
+
+/// Checks a graph in the current format (see `migrate::upgrade` for files read
+/// from disk). Findings come back ordered by node id.
+pub async fn validate(raw: Value) -> Vec<Finding> {
+    let graph = match Graph::from_json(raw) {
+        Ok(graph) => graph,
+        Err(message) => return vec![Finding::error("invalid-format", message)],
+    };
+    let mut findings = Vec::new();
+    check_links(&graph, &mut findings);
+    // id 重复已经报过 duplicate-node
+    if graph.duplicate_id().is_none() {
+        if let Err(message) = graph.topo_order() {
+            findings.push(Finding::error("cycle", message));
+        }
+    }
+    for node in &graph.nodes {
+        check_node(node, &mut findings);
+    }
+    check_syntax(&graph, &mut findings).await;
+    for node in &graph.nodes {
+        match node.code_type() {
+            Some("subgraph") => {
+                let inputs = node.inputs().iter().map(|p| p.name.as_str()).collect();
+                let outputs = node.outputs().iter().map(|p| p.name.as_str()).collect();
+                check_subgraph(
+                    node,
+                    [("input", inputs, true), ("output", outputs, true)],
+                    &mut findings,
+                )
+                .await;
+            }
+            // Map 的子图：item / index 可以不用，result 必须有
+            Some("map") if node.body_type() == Some("subgraph") => {
+                let ports = [
+                    ("input", vec!["item", "index"], false),
+                    ("output", vec!["result"], true),
+                ];
+                check_subgraph(node, ports, &mut findings).await;
+            }
+            // 循环的子图：state 进 state 出
+            Some("while" | "repeat") if node.body_type() == Some("subgraph") => {
+                let ports = [
+                    ("input", vec!["state", "index"], false),
+                    ("output", vec!["state"], true),
+                ];
+                check_subgraph(node, ports, &mut findings).await;
+            }
+            _ => {}
+        }
+    }
+    findings.sort_by_key(|finding| finding.node_id);
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn check(raw: Value) -> Vec<(String, String, Option<u64>)> {
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(validate(raw))
+            .into_iter()
+            .map(|finding| (finding.severity, finding.code, finding.node_id))
+            .collect()
+    }
+
+    fn node(id: u64, properties: Value) -> Value {
+        json!({
+            "id": id,
+            "type": "code/Node",
+            "inputs": [{"name": "in", "type": "*", "link": null}],
+            "outputs": [{"name": "out", "type": "*", "links": []}],
+            "properties": properties
+        })
+    }
+
+    fn rustlite(id: u64) -> Value {
+        node(id, json!({"codeType": "rustlite", "fn": "1"}))
+    }
+
+    // 1 -> 2，端口和 link 两头都对得上
+    fn linked(origin: Value, target: Value) -> Value {
+        let mut nodes = [origin, target];
+        nodes[0]["outputs"][0]["links"] = json!([1]);
+        nodes[1]["inputs"][0]["link"] = json!(1);
+        json!({"nodes": nodes, "links": [[1, 1, 0, 2, 0, "*"]]})
+    }
+
+    fn alone(node: Value) -> Value {
+        json!({"nodes": [node], "links": []})
+    }
+
+    fn error(code: &str, node: Option<u64>) -> (String, String, Option<u64>) {
+        ("error".to_string(), code.to_string(), node)
+    }
+
+    fn warning(code: &str, node: Option<u64>) -> (String, String, Option<u64>) {
+        ("warning".to_string(), code.to_string(), node)
+    }
+
+    #[test]
+    fn clean() {
+        assert!(check(linked(rustlite(1), rustlite(2))).is_empty());
+    }
+
+    #[test]
+    fn invalid_format() {
+        assert_eq!(check(json!({"nodes": 1})), [error("invalid-format", None)]);
+    }
+
+    #[test]
+    fn duplicate_node() {
+        let found = check(json!({"nodes": [rustlite(1), rustlite(1)], "links": []}));
+        assert_eq!(found, [error("duplicate-node", Some(1))]);
+    }
+
+    #[test]
+    fn duplicate_link() {
+        let mut raw = linked(rustlite(1), rustlite(2));
+        raw["links"] = json!([[1, 1, 0, 2, 0, "*"], [1, 1, 0, 2, 0, "*"]]);
+        assert!(check(raw).contains(&error("duplicate-link", None)));
+    }
+
+    #[test]
+    fn missing_node() {
+        let mut raw = linked(rustlite(1), rustlite(2));
+        raw["links"] = json!([[1, 1, 0, 9, 0, "*"]]);
+        assert!(check(raw).contains(&error("missing-node", None)));
+    }
+
+    #[test]
+    fn bad_slot() {
+        let mut raw = linked(rustlite(1), rustlite(2));
+        raw["links"] = json!([[1, 1, 3, 2, 0, "*"]]);
+        assert_eq!(check(raw), [error("bad-slot", Some(1))]);
+    }
+
+    #[test]
+    fn type_mismatch() {
+        let mut raw = linked(rustlite(1), rustlite(2));
+        raw["nodes"][0]["outputs"][0]["type"] = json!("number");
+        raw["nodes"][1]["inputs"][0]["type"] = json!("string,boolean");
+        assert_eq!(check(raw.clone()), [error("type-mismatch", Some(2))]);
+        raw["nodes"][1]["inputs"][0]["type"] = json!("string,Number");
+        assert!(check(raw).is_empty());
+    }
+
+    #[test]
+    fn link_mismatch() {
+        let mut raw = linked(rustlite(1), rustlite(2));
+        raw["nodes"][0]["outputs"][0]["links"] = json!([]);
+        assert_eq!(check(raw), [warning("link-mismatch", Some(1))]);
+    }
+
+    #[test]
+    fn dangling_link() {
+        let mut raw = alone(rustlite(1));
+        raw["nodes"][0]["inputs"][0]["link"] = json!(5);
+        assert_eq!(check(raw), [error("dangling-link", Some(1))]);
+    }
+
+    #[test]
+    fn cycle() {
+        let mut raw = linked(rustlite(1), rustlite(2));
+        raw["nodes"][1]["outputs"][0]["links"] = json!([2]);
+        raw["nodes"][0]["inputs"][0]["link"] = json!(2);
+        raw["links"] = json!([[1, 1, 0, 2, 0, "*"], [2, 2, 0, 1, 0, "*"]]);
+        assert_eq!(check(raw), [error("cycle", None)]);
+    }
+
+    #[test]
+    fn code_types() {
+        assert_eq!(
+            check(alone(node(1, json!({"fn": "1"})))),
+            [error("missing-code-type", Some(1))]
+        );
+        assert_eq!(
+            check(alone(node(1, json!({"codeType": "cobol", "fn": "1"})))),
+            [error("unknown-code-type", Some(1))]
+        );
+    }
+
+    #[test]
+    fn missing_path() {
+        assert_eq!(
+            check(alone(node(1, json!({"codeType": "call", "path": " "})))),
+            [error("missing-path", Some(1))]
+        );
+    }
+
+    #[test]
+    fn bad_body() {
+        assert_eq!(
+            check(alone(node(1, json!({"codeType": "map", "fn": "1"})))),
+            [error("bad-body", Some(1))]
+        );
+        let properties = json!({"codeType": "map", "bodyType": "if", "fn": "1"});
+        assert_eq!(
+            check(alone(node(1, properties))),
+            [error("bad-body", Some(1))]
+        );
+    }
+
+    #[test]
+    fn empty_code() {
+        assert_eq!(
+            check(alone(node(1, json!({"codeType": "python", "fn": " "})))),
+            [warning("empty-code", Some(1))]
+        );
+        let properties = json!({"codeType": "map", "bodyType": "rustlite"});
+        assert_eq!(
+            check(alone(node(1, properties))),
+            [warning("empty-code", Some(1))]
+        );
+        // If / Switch 留空是合法的
+        let mut branch = node(1, json!({"codeType": "if"}));
+        branch["outputs"] = json!([{"name": "then", "links": []}, {"name": "else", "links": []}]);
+        assert!(check(alone(branch)).is_empty());
+        // one2oneTerminal 的命令从输入口来
+        assert!(check(alone(node(1, json!({"codeType": "one2one"})))).is_empty());
+    }
+
+    #[test]
+    fn bad_branch() {
+        assert_eq!(
+            check(alone(node(1, json!({"codeType": "if"})))),
+            [error("bad-branch", Some(1))]
+        );
+        let mut switch = node(1, json!({"codeType": "switch"}));
+        switch["outputs"] = json!([]);
+        assert_eq!(check(alone(switch)), [warning("bad-branch", Some(1))]);
+    }
+
+    #[test]
+    fn bad_timeout() {
+        for timeout in [json!(1e20), json!(-1), json!("10")] {
+            let properties = json!({"codeType": "rustlite", "fn": "1", "timeout": timeout});
+            assert_eq!(
+                check(alone(node(1, properties))),
+                [error("bad-timeout", Some(1))]
+            );
+        }
+        let properties = json!({"codeType": "rustlite", "fn": "1", "timeout": 2.5});
+        assert!(check(alone(node(1, properties))).is_empty());
+    }
+
+    #[test]
+    fn bad_retry() {
+        for retry in [
+            json!({"maxAttempts": 0}),
+            json!({"delay": 1e20}),
+            json!({"tries": 2}),
+        ] {
+            let properties = json!({"codeType": "rustlite", "fn": "1", "retry": retry});
+            assert_eq!(
+                check(alone(node(1, properties))),
+                [error("bad-retry", Some(1))]
+            );
+        }
+    }
+
+    #[test]
+    fn bad_loop() {
+        let properties = json!({
+            "codeType": "while", "bodyType": "rustlite", "fn": "1", "maxIterations": 0
+        });
+        assert_eq!(
+            check(alone(node(1, properties))),
+            [error("bad-loop", Some(1))]
+        );
+        let properties = json!({
+            "codeType": "repeat", "bodyType": "rustlite", "fn": "1",
+            "count": 20, "maxIterations": 10
+        });
+        assert_eq!(
+            check(alone(node(1, properties))),
+            [error("bad-loop", Some(1))]
+        );
+    }
+
+    #[test]
+    fn syntax() {
+        assert_eq!(
+            check(alone(node(1, json!({"codeType": "rustlite", "fn": "1 +"})))),
+            [error("syntax", Some(1))]
+        );
+        let properties = json!({
+            "codeType": "while", "bodyType": "rustlite", "fn": "1", "condition": "(("
+        });
+        assert_eq!(
+            check(alone(node(1, properties))),
+            [error("syntax", Some(1))]
+        );
+    }
+
+    #[test]
+    fn subgraphs() {
+        assert_eq!(
+            check(alone(node(1, json!({"codeType": "subgraph"})))),
+            [error("missing-subgraph", Some(1))]
+        );
+
+        // 外面的端口是 in / out，里面只有一个名字对不上的 graph/output
+        let mut outer = node(1, json!({"codeType": "subgraph"}));
+        let boundary = node(5, json!({"codeType": "output", "name": "other"}));
+        let broken = node(6, json!({"codeType": "rustlite", "fn": "1 +"}));
+        outer["subgraph"] = json!({"nodes": [boundary, broken], "links": []});
+        let found = check(alone(outer));
+        assert_eq!(
+            found,
+            [
+                // 子图里的问题挂在外面的子图节点上
+                error("syntax", Some(1)),
+                error("subgraph-port", Some(1)),
+                error("subgraph-port", Some(1)),
+                warning("subgraph-port", Some(1)),
+            ]
+        );
+    }
+}
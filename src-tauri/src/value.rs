@@ -0,0 +1,200 @@
+// 所有节点运行时共用的数据模型
+// JSON 上大部分值保持原样，只有 JSON 表达不了的值用单 key 对象打标签：
+//   {"$int": "9007199254740993"}  超出 JS 安全整数范围的 i64
+//   {"$float": "NaN"}             NaN / inf / -inf
+//   {"$bytes": "<base64>"}        二进制
+//   {"$error": "message"}         错误值
+//   {"$map": {...}}               本身恰好长得像标签的 map
+use crate::rust_diagnostics::Diagnostic;
+use base64::Engine as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+// 2^53 - 1，JS Number 能精确表示的最大整数
+const MAX_SAFE_INTEGER: i64 = 9_007_199_254_740_991;
+const TAGS: &[&str] = &["$int", "$float", "$bytes", "$error", "$map"];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+    Error(String),
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(json: serde_json::Value) -> Self {
+        use serde_json::Value as Json;
+        match json {
+            Json::Null => Value::Null,
+            Json::Bool(b) => Value::Bool(b),
+            Json::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            Json::String(s) => Value::String(s),
+            Json::Array(items) => Value::List(items.into_iter().map(Value::from).collect()),
+            Json::Object(mut map) => {
+                if map.len() == 1 {
+                    let (tag, _) = map.iter().next().unwrap();
+                    if TAGS.contains(&tag.as_str()) {
+                        let tag = tag.clone();
+                        let inner = map.remove(&tag).unwrap();
+                        if let Some(value) = untag(&tag, inner.clone()) {
+                            return value;
+                        }
+                        // 标签内容不合法时按普通 map 处理
+                        map.insert(tag, inner);
+                    }
+                }
+                Value::Map(map.into_iter().map(|(k, v)| (k, Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+fn untag(tag: &str, inner: serde_json::Value) -> Option<Value> {
+    let text = inner.as_str();
+    match tag {
+        "$int" => text?.parse().ok().map(Value::Int),
+        "$float" => match text? {
+            "NaN" => Some(Value::Float(f64::NAN)),
+            "inf" => Some(Value::Float(f64::INFINITY)),
+            "-inf" => Some(Value::Float(f64::NEG_INFINITY)),
+            other => other.parse().ok().map(Value::Float),
+        },
+        "$bytes" => base64::engine::general_purpose::STANDARD
+            .decode(text?)
+            .ok()
+            .map(Value::Bytes),
+        "$error" => Some(Value::Error(text?.to_string())),
+        "$map" => match inner {
+            serde_json::Value::Object(map) => Some(Value::Map(
+                map.into_iter().map(|(k, v)| (k, Value::from(v))).collect(),
+            )),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn tagged(tag: &str, inner: serde_json::Value) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+    map.insert(tag.to_string(), inner);
+    serde_json::Value::Object(map)
+}
+
+impl From<&Value> for serde_json::Value {
+    fn from(value: &Value) -> Self {
+        use serde_json::Value as Json;
+        match value {
+            Value::Null => Json::Null,
+            Value::Bool(b) => Json::Bool(*b),
+            Value::Int(i) if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(i) => Json::from(*i),
+            Value::Int(i) => tagged("$int", Json::String(i.to_string())),
+            Value::Float(f) if f.is_finite() => Json::from(*f),
+            Value::Float(f) => tagged(
+                "$float",
+                Json::String(if f.is_nan() {
+                    "NaN".to_string()
+                } else if *f > 0.0 {
+                    "inf".to_string()
+                } else {
+                    "-inf".to_string()
+                }),
+            ),
+            Value::String(s) => Json::String(s.clone()),
+            Value::Bytes(bytes) => tagged(
+                "$bytes",
+                Json::String(base64::engine::general_purpose::STANDARD.encode(bytes)),
+            ),
+            Value::List(items) => Json::Array(items.iter().map(Json::from).collect()),
+            Value::Map(map) => {
+                let object: serde_json::Map<_, _> = map
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Json::from(v)))
+                    .collect();
+                if object.len() == 1 && TAGS.contains(&object.keys().next().unwrap().as_str()) {
+                    tagged("$map", Json::Object(object))
+                } else {
+                    Json::Object(object)
+                }
+            }
+            Value::Error(message) => tagged("$error", Json::String(message.clone())),
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_json::Value::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_json::Value::deserialize(deserializer).map(Value::from)
+    }
+}
+
+/// The `{error, labelMarkedForOutputs, result|details}` object every node command returns.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    pub error: bool,
+    pub label_marked_for_outputs: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// Compiler diagnostics for Rust nodes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+    /// What the node printed, for runtimes that redirect prints away from the result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+    /// What the node wrote to stderr.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+}
+
+impl Envelope {
+    pub fn ok(result: Value) -> Self {
+        Self {
+            error: false,
+            label_marked_for_outputs: "rawResult".to_string(),
+            result: Some(result),
+            details: None,
+            diagnostics: Vec::new(),
+            stdout: None,
+            stderr: None,
+        }
+    }
+
+    pub fn err(details: impl Into<String>) -> Self {
+        Self {
+            error: true,
+            label_marked_for_outputs: "rawResult".to_string(),
+            result: None,
+            details: Some(details.into()),
+            diagnostics: Vec::new(),
+            stdout: None,
+            stderr: None,
+        }
+    }
+}
+
+impl From<Result<Value, String>> for Envelope {
+    fn from(result: Result<Value, String>) -> Self {
+        match result {
+            Ok(value) => Envelope::ok(value),
+            Err(details) => Envelope::err(details),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+// WebAssembly 节点：在内嵌的 wasmtime + WASI 沙箱里运行
+// inputs 以 JSON 写入 stdin，stdout 读出结果；没有预打开目录、环境变量和网络
+use crate::value::{Envelope, Value};
+use base64::Engine as _;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use wasmtime::{
+    Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap, UpdateDeadline,
+};
+use wasmtime_wasi::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{I32Exit, WasiCtxBuilder};
+
+const DEFAULT_FUEL: u64 = 1_000_000_000;
+const DEFAULT_MEMORY_MB: u64 = 64;
+const MAX_OUTPUT_BYTES: usize = 16 * 1024 * 1024;
+// 多久检查一次中断标志
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy)]
+pub struct Limits {
+    pub fuel: u64,
+    pub memory_bytes: usize,
+}
+
+impl Limits {
+    /// Reads `properties.fuel` and `properties.memoryLimitMb`, falling back to defaults.
+    pub fn from_properties(properties: &serde_json::Value) -> Self {
+        let fuel = properties["fuel"].as_u64().unwrap_or(DEFAULT_FUEL);
+        let memory_mb = properties["memoryLimitMb"]
+            .as_u64()
+            .unwrap_or(DEFAULT_MEMORY_MB);
+        Self {
+            fuel,
+            // 填得离谱的值按上限算，别溢出
+            memory_bytes: memory_mb.saturating_mul(1024 * 1024).min(usize::MAX as u64) as usize,
+        }
+    }
+}
+
+struct State {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+fn engine() -> Result<&'static Engine, String> {
+    static ENGINE: OnceLock<Result<Engine, String>> = OnceLock::new();
+    ENGINE
+        .get_or_init(|| {
+            let mut config = Config::new();
+            config.consume_fuel(true);
+            config.epoch_interruption(true);
+            let engine = Engine::new(&config).map_err(|e| e.to_string())?;
+            // epoch 每个 tick 加一，到期时 store 的回调看一眼中断标志
+            let ticker = engine.clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(EPOCH_TICK);
+                ticker.increment_epoch();
+            });
+            Ok(engine)
+        })
+        .as_ref()
+        .map_err(|e| e.clone())
+}
+
+// properties.fn 可以是 WAT 文本，也可以是 .wasm 的 base64
+fn module_bytes(code: &str) -> Result<Vec<u8>, String> {
+    let code = code.trim();
+    if code.starts_with('(') {
+        return Ok(code.as_bytes().to_vec());
+    }
+    let encoded = code
+        .strip_prefix("data:application/wasm;base64,")
+        .unwrap_or(code);
+    let encoded: String = encoded.split_whitespace().collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("node code is neither WAT text nor base64 wasm: {}", e))
+}
+
+fn run(
+    code: &str,
+    inputs: &Value,
+    limits: Limits,
+    interrupt: Option<Arc<AtomicBool>>,
+) -> Result<Value, String> {
+    let engine = engine()?;
+    let module =
+        Module::new(engine, module_bytes(code)?).map_err(|e| format!("invalid module: {:#}", e))?;
+
+    let stdin = MemoryInputPipe::new(serde_json::to_vec(inputs).map_err(|e| e.to_string())?);
+    let stdout = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+    let stderr = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+    let wasi = WasiCtxBuilder::new()
+        .stdin(stdin)
+        .stdout(stdout.clone())
+        .stderr(stderr.clone())
+        .build_p1();
+    let state = State {
+        wasi,
+        limits: StoreLimitsBuilder::new()
+            .memory_size(limits.memory_bytes)
+            .instances(1)
+            .build(),
+    };
+    let mut store = Store::new(engine, state);
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(limits.fuel).map_err(|e| e.to_string())?;
+    store.set_epoch_deadline(1);
+    store.epoch_deadline_callback(move |_| match &interrupt {
+        Some(interrupt) if interrupt.load(Ordering::Relaxed) => {
+            Err(wasmtime::Error::msg("interrupted"))
+        }
+        _ => Ok(UpdateDeadline::Continue(1)),
+    });
+
+    let mut linker: Linker<State> = Linker::new(engine);
+    preview1::add_to_linker_sync(&mut linker, |state| &mut state.wasi)
+        .map_err(|e| e.to_string())?;
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .map_err(|e| format!("instantiate failed: {:#}", e))?;
+    let start = instance
+        .get_typed_func::<(), ()>(&mut store, "_start")
+        .map_err(|_| "module has no `_start` export".to_string())?;
+
+    if let Err(e) = start.call(&mut store, ()) {
+        // proc_exit(0) 也会以 I32Exit 的形式返回
+        match e.downcast_ref::<I32Exit>() {
+            Some(I32Exit(0)) => {}
+            Some(I32Exit(code)) => {
+                return Err(format!(
+                    "exit code {}: {}",
+                    code,
+                    String::from_utf8_lossy(&stderr.contents())
+                ))
+            }
+            None => {
+                return Err(match e.downcast_ref::<Trap>() {
+                    Some(Trap::OutOfFuel) => format!("fuel exhausted (limit {})", limits.fuel),
+                    _ => format!("{:#}", e),
+                })
+            }
+        }
+    }
+
+    let out = stdout.contents();
+    if out.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(Value::Null);
+    }
+    // stdout 不是 JSON 时按字符串返回，不是 UTF-8 时按二进制返回
+    Ok(
+        serde_json::from_slice(&out).unwrap_or_else(|_| match String::from_utf8(out.to_vec()) {
+            Ok(text) => Value::String(text),
+            Err(e) => Value::Bytes(e.into_bytes()),
+        }),
+    )
+}
+
+/// Runs a WASI command module with `inputs` as JSON on stdin and returns the
+/// same envelope as `python_exec`. Blocking; call it off the async runtime.
+pub fn exec(code: &str, inputs: &Value, limits: Limits) -> Envelope {
+    run(code, inputs, limits, None).into()
+}
+
+/// Like `exec`, but traps with an error once `interrupt` is set.
+pub fn exec_interruptible(
+    code: &str,
+    inputs: &Value,
+    limits: Limits,
+    interrupt: Arc<AtomicBool>,
+) -> Envelope {
+    run(code, inputs, limits, Some(interrupt)).into()
+}
@@ -19,6 +19,10 @@ function updateDebugStatus() {
         statusIndicator.className = "status-indicator status-paused";
         statusIndicator.innerHTML = '<div class="status-dot"></div><span>paused</span>';
         btnStop.classList.add("active");
+    } else if (debugState === "replay") {
+        statusIndicator.className = "status-indicator status-paused";
+        statusIndicator.innerHTML = `<div class="status-dot"></div><span>replay ${replayFrame.cursor}/${replayFrame.len}</span>`;
+        btnStop.classList.add("active");
     } else if (debugState === "stopped") {
         statusIndicator.className = "status-indicator status-stopped";
         statusIndicator.innerHTML = '<div class="status-dot"></div><span>stopped</span>';
@@ -26,52 +30,194 @@ function updateDebugStatus() {
     }
 }
 
+// 开始运行：step 为 true 时停在第一个节点前面
+function startRun(step) {
+    // 状态由后端的执行事件驱动，这里只处理还没开始跑就失败的情况（比如图里有环）
+    runStarting = true;
+    run_graph(step)
+        .then((report) => lastRunHistoryId = report.historyId)
+        .catch((error) => {
+            // 没开始跑就失败了，不会有 run-started
+            runStarting = false;
+            stopRequested = false;
+            showHint('!!!' + error + '!!!');
+            resetToolbar();
+        });
+    showRunningToolbar();
+}
+
+// 回放：Return 往回退一个节点，Next 往前走，Stop 回到运行结束时的样子并退出
+let lastRunHistoryId = null;
+let replayFrame = null;
+
+// 把某一步的输出和连线上的值摆回画布，不执行节点
+function applyFrame(frame) {
+    replayFrame = frame;
+    const done = new Map(frame.nodes.map((node) => [node.id, node]));
+    window.graph._nodes.forEach((node) => {
+        const recorded = done.get(node.id);
+        (node.outputs || []).forEach((_, i) => {
+            node.setOutputData(i, recorded ? decodeValue(recorded.outputs[i] ?? null) : undefined);
+        });
+        const status = recorded && (recorded.status === "skipped" ? "skipped" : recorded.error === null ? "finished" : "failed");
+        markNode(node.id, status || null);
+    });
+    frame.links.forEach((link) => {
+        if (window.graph.links[link.id]) {
+            window.graph.links[link.id].data = decodeValue(link.value);
+            window.graph.links[link.id].color = link.inactive ? INACTIVE_LINK_COLOR : undefined;
+        }
+    });
+    if (frame.current !== null) {
+        markNode(frame.current, "paused");
+    }
+    debugState = "replay";
+    updateDebugStatus();
+}
+
+async function replayStep(delta) {
+    try {
+        if (debugState !== "replay") {
+            if (lastRunHistoryId === null) {
+                showHint("run the graph first");
+                return;
+            }
+            await invoke("replay_open", { runId: lastRunHistoryId });
+            showPausedToolbar();
+        }
+        applyFrame(await invoke("replay_step", { delta }));
+    } catch (error) {
+        showHint('!!!' + error + '!!!');
+    }
+}
+
+async function leaveReplay() {
+    try {
+        applyFrame(await invoke("replay_seek", { cursor: replayFrame.len }));
+        await invoke("replay_close");
+    } catch (error) {
+        showHint('!!!' + error + '!!!');
+    }
+    replayFrame = null;
+    resetToolbar();
+}
+
+function showRunningToolbar() {
+    btnRun.style.display = "none";
+    btnNext.style.width = "0";
+    btnStop.style.display = "flex";
+}
+
+// 停在断点上：Next 单步，Run 继续，Stop 结束
+function showPausedToolbar() {
+    btnRun.style.display = "flex";
+    btnNext.removeAttribute("style");
+    btnStop.style.display = "flex";
+}
+
+function resetToolbar() {
+    debugState = "stopped";
+    updateDebugStatus();
+    btnStop.style.display = "none";
+    btnNext.removeAttribute("style");
+    btnRun.style.display = "flex";
+}
+
+// 给暂停中的运行发命令：step / continue / stop
+function resume(command) {
+    debugState = "running";
+    updateDebugStatus();
+    showRunningToolbar();
+    invoke("graph_debug", { runId: currentRunId, command }).catch((error) => showHint('!!!' + error + '!!!'));
+}
+
 // 按钮点击事件处理
 btnNext.addEventListener("click", function () {
-    if (debugState === "paused") {
-        debugState = "running";
-        updateDebugStatus();
+    if (debugState === "replay") {
+        replayStep(1);
+    } else if (debugState === "paused") {
+        resume("step");
+    } else if (debugState === "stopped") {
+        startRun(true);
     }
 
     this.classList.add("active");
-    // receive run out one event todo (clear)
     setTimeout(() => this.classList.remove("active"), 200);
 });
 
-btnRun.addEventListener("click", function () {
-    window.graph.runStep();
-    debugState = "running";
-    updateDebugStatus();
-    this.style.display = "none";
-    btnNext.style.width = "0";
-    btnStop.style.display = "flex";
+btnRun.addEventListener("click", async function () {
+    if (debugState === "paused") {
+        resume("continue");
+    } else if (debugState === "replay") {
+        await leaveReplay();
+        startRun(false);
+    } else {
+        startRun(false);
+    }
 });
 
+// 按 run id 取消这一次运行
+function cancelRun(runId) {
+    invoke("graph_cancel", { runId }).catch((error) => {
+        showHint('!!!' + error + '!!!');
+        resetToolbar();
+    });
+}
+
+// 暂停时让后端结束这次运行，跑着的时候取消它，run-finished 回来再复位按钮；
+// run-started 还没到时不知道 run id，先记下来，到了再取消
 btnStop.addEventListener("click", function () {
-    debugState = "stopped";
-    updateDebugStatus();
-    this.style.display = "none";
-    btnNext.attributes.removeNamedItem('style');
-    btnRun.style.display = "flex";
+    if (debugState === "paused") {
+        resume("stop");
+    } else if (debugState === "running") {
+        if (runStarting) {
+            stopRequested = true;
+        } else {
+            cancelRun(currentRunId);
+        }
+    } else if (debugState === "replay") {
+        leaveReplay();
+    } else {
+        resetToolbar();
+    }
 });
 
 btnRetrun.addEventListener("click", function () {
-    debugState = "paused";
-    updateDebugStatus();
+    if (debugState === "stopped" || debugState === "replay") {
+        replayStep(-1);
+    }
     this.classList.add("active");
     setTimeout(() => this.classList.remove("active"), 200);
 });
 
-btnToggleBreakpoint.addEventListener("click", function () {
-    const isActive = this.classList.contains("active");
+// 断点存在节点的 properties.breakpoint 里，跟着图一起保存
+function selectedNodes() {
+    return Object.values(window.canvas.selected_nodes || {});
+}
 
-    if (isActive) {
-        this.classList.remove("active");
-        console.log(`在第${null}行移除断点`);
-    } else {
-        this.classList.add("active");
-        console.log(`在第${null}行设置断点`);
+function showBreakpointState() {
+    const nodes = selectedNodes();
+    btnToggleBreakpoint.classList.toggle("active", nodes.length > 0 && nodes.every((node) => node.properties.breakpoint));
+}
+
+document.addEventListener("nodeSelected", showBreakpointState);
+
+btnToggleBreakpoint.addEventListener("click", function () {
+    const nodes = selectedNodes();
+    if (!nodes.length) {
+        showHint("select a node to toggle its breakpoint");
+        return;
     }
+    const enable = !nodes.every((node) => node.properties.breakpoint);
+    nodes.forEach((node) => {
+        if (enable) {
+            node.properties.breakpoint = true;
+        } else {
+            delete node.properties.breakpoint;
+        }
+    });
+    window.graph.setDirtyCanvas(true, true);
+    showBreakpointState();
 
     // 添加点击效果
     this.style.transform = "scale(0.95)";
@@ -113,6 +259,139 @@ document.addEventListener("keydown", function (event) {
     }
 });
 
+// 后端执行事件：run-started / paused / node-started / node-retrying / node-finished / node-failed / node-skipped / run-finished
+// 节点标题栏的小方块显示状态：停住蓝色，跑着黄色，等待重试紫色，完成绿色，失败红色，超时橙色，取消灰色，跳过深灰
+const NODE_STATUS_COLOR = {
+    paused: "#4a90d9",
+    running: "#e6b422",
+    retrying: "#b07cd8",
+    finished: "#3cb371",
+    failed: "#e0433b",
+    timedOut: "#e07b39",
+    cancelled: "#888888",
+    skipped: "#555555"
+};
+// If / Switch 没走的分支：从 inactive 输出口出去的连线
+const INACTIVE_LINK_COLOR = "#444444";
+let currentRunId = null;
+// startRun 之后、run-started 之前
+let runStarting = false;
+let stopRequested = false;
+const activeNodes = new Map(); // id -> title
+
+function markNode(id, status) {
+    const node = window.graph.getNodeById(id);
+    if (!node) {
+        return;
+    }
+    node.boxcolor = status ? NODE_STATUS_COLOR[status] : undefined;
+    window.graph.setDirtyCanvas(true, true);
+}
+
+function dimLinks(id, inactive) {
+    const node = window.graph.getNodeById(id);
+    (node?.outputs || []).forEach((output, slot) => {
+        (output.links || []).forEach((linkId) => {
+            const link = window.graph.links[linkId];
+            if (link) {
+                link.color = inactive.includes(slot) ? INACTIVE_LINK_COLOR : undefined;
+            }
+        });
+    });
+}
+
+function showActiveNodes() {
+    if (debugState !== "running") {
+        return;
+    }
+    const titles = [...activeNodes.values()];
+    statusIndicator.querySelector("span").textContent = titles.length
+        ? `running ${titles[0]}${titles.length > 1 ? ` +${titles.length - 1}` : ""}`
+        : "running";
+}
+
+const { listen: listenExecution } = window.__TAURI__.event;
+
+listenExecution("run-started", ({ payload }) => {
+    currentRunId = payload.runId;
+    if (runStarting) {
+        runStarting = false;
+        if (stopRequested) {
+            stopRequested = false;
+            cancelRun(payload.runId);
+        }
+    }
+    activeNodes.clear();
+    window.graph._nodes.forEach((node) => {
+        markNode(node.id, null);
+        dimLinks(node.id, []);
+    });
+    debugState = "running";
+    updateDebugStatus();
+});
+
+// 暂停在节点前面，inputs 是它马上要拿到的输入
+listenExecution("paused", ({ payload }) => {
+    if (payload.runId !== currentRunId) return;
+    const node = window.graph.getNodeById(payload.id);
+    if (node) {
+        node.pendingInputs = decodeValue(payload.inputs);
+    }
+    markNode(payload.id, "paused");
+    debugState = "paused";
+    updateDebugStatus();
+    statusIndicator.querySelector("span").textContent = `paused ${payload.title}`;
+    showPausedToolbar();
+    const preview = JSON.stringify(payload.inputs);
+    showHint(`paused before ${payload.title}<br>${preview.length > 200 ? preview.slice(0, 200) + '…' : preview}`);
+});
+
+listenExecution("node-started", ({ payload }) => {
+    if (payload.runId !== currentRunId) return;
+    activeNodes.set(payload.id, payload.attempt > 1 ? `${payload.title} #${payload.attempt}` : payload.title);
+    markNode(payload.id, "running");
+    showActiveNodes();
+});
+
+// properties.retry：attempt 是刚失败的那次，delayMs 之后再跑
+listenExecution("node-retrying", ({ payload }) => {
+    if (payload.runId !== currentRunId) return;
+    markNode(payload.id, "retrying");
+    showHint(`${payload.title} attempt ${payload.attempt} failed, retrying in ${(payload.delayMs / 1000).toFixed(1)}s`);
+});
+
+listenExecution("node-finished", ({ payload }) => {
+    if (payload.runId !== currentRunId) return;
+    activeNodes.delete(payload.id);
+    markNode(payload.id, "finished");
+    dimLinks(payload.id, payload.inactive);
+    showActiveNodes();
+});
+
+listenExecution("node-failed", ({ payload }) => {
+    if (payload.runId !== currentRunId) return;
+    activeNodes.delete(payload.id);
+    // status: failed / timedOut / cancelled
+    markNode(payload.id, payload.status in NODE_STATUS_COLOR ? payload.status : "failed");
+    showActiveNodes();
+});
+
+// 连着线的输入全来自没走的分支，节点没跑，它的输出也都不走
+listenExecution("node-skipped", ({ payload }) => {
+    if (payload.runId !== currentRunId) return;
+    markNode(payload.id, "skipped");
+    const node = window.graph.getNodeById(payload.id);
+    dimLinks(payload.id, (node?.outputs || []).map((_, slot) => slot));
+});
+
+listenExecution("run-finished", ({ payload }) => {
+    if (payload.runId !== currentRunId) return;
+    // 还没开始就被取消的节点没有事件，去掉它们的高亮
+    activeNodes.forEach((_, id) => markNode(id, null));
+    activeNodes.clear();
+    resetToolbar();
+});
+
 // 初始状态 调试状态
-let debugState = "stopped"; // running, paused, stopped
+let debugState = "stopped"; // running, paused, replay, stopped
 updateDebugStatus();
@@ -1,5 +1,31 @@
 
 // -------------------- Derived Nodes ----------------------
+// from 顺着输出的连线能不能走到 to（from === to 也算）
+function reaches(graph, from, to) {
+    const seen = new Set();
+    const stack = [from];
+    while (stack.length) {
+        const node = stack.pop();
+        if (node === to) {
+            return true;
+        }
+        if (seen.has(node.id)) {
+            continue;
+        }
+        seen.add(node.id);
+        (node.outputs || []).forEach((output) => {
+            (output.links || []).forEach((id) => {
+                const link = graph.links[id];
+                const target = link && graph.getNodeById(link.target_id);
+                if (target) {
+                    stack.push(target);
+                }
+            });
+        });
+    }
+    return false;
+}
+
 class runtimeNode {
     constructor() {
         this.serialize_widgets = true;
@@ -31,7 +57,13 @@ class runtimeNode {
                         result = await executeTerminalCommand(this.properties.fn, inputs);
                     break;
                     case "rust":
-                        result = await rust_exec(this.properties.fn, inputs);
+                        result = await rust_exec(this.properties.fn, inputs, this.properties.deps);
+                    break;
+                    case "rustlite":
+                        result = await rust_eval(this.properties.fn, inputs);
+                    break;
+                    case "wasm":
+                        result = await wasm_exec(this.properties.fn, inputs, this.properties);
                     break;
                     default:
                         console.log('unknown type');
@@ -47,6 +79,12 @@ class runtimeNode {
             // debug
             // console.log(result);
 
+            // rustc diagnostics, rendered as markers when this node is selected
+            this.diagnostics = (result && result.diagnostics) || [];
+            document.dispatchEvent(new CustomEvent('nodeDiagnostics', {
+                detail: this
+            }));
+
             // test todo
             if (result === undefined || result === null) {
                 // 如果没有返回值，清空所有输出
@@ -61,11 +99,11 @@ class runtimeNode {
                 switch (result.labelMarkedForOutputs) {
                     case 'rawResult':
                         // object made for error check
-                        if (result.error = true) {
+                        if (result.error === true) {
                             for (let i = 0; i < this.outputs.length; i++) {
                                 this.setOutputData(i, undefined);
                             }
-                            console.error("Node"+this.id+"run ERROR: ", result.detail);
+                            console.error("Node"+this.id+"run ERROR: ", result.details ?? result.detail);
                         } else if (result.result) {
                             for (let i = 0; i < this.outputs.length; i++) {
                                 this.setOutputData(i, result.result);
@@ -109,6 +147,31 @@ class runtimeNode {
             console.error(this.id+"号节点执行错误:", err);
         }
     }
+    // 断点：标题栏右侧画一个红点
+    onDrawForeground(ctx) {
+        if (!this.properties.breakpoint || this.flags.collapsed) {
+            return;
+        }
+        ctx.fillStyle = "#e0433b";
+        ctx.beginPath();
+        ctx.arc(this.size[0] - 10, -LiteGraph.NODE_TITLE_HEIGHT / 2, 5, 0, Math.PI * 2);
+        ctx.fill();
+    }
+    // 不许连出环：从这个节点顺着输出能走回 origin 就拒绝，要循环用 While / Repeat
+    onConnectInput(slot, type, output, origin) {
+        if (origin && this.graph && reaches(this.graph, this, origin)) {
+            showHint('!!!this link would make a cycle, use a While or Repeat node to loop!!!');
+            return false;
+        }
+        return true;
+    }
+    // 右键菜单：把当前选中的节点收成一个子图节点
+    getExtraMenuOptions(canvas) {
+        return [{
+            content: "Collapse selection to subgraph",
+            callback: () => collapseToSubgraph(canvas)
+        }];
+    }
     onSelected() {
         console.log("当前选中节点id:", this.id);
 
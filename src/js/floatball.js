@@ -45,10 +45,14 @@ function triggerAction(action) {
         case 'save':
             exportGraph();
         break;
+        case 'script':
+            exportScript();
+        break;
         case 'import':
             importGraph()
         break;
-        case 'settings':
+        case 'setting':
+            pickRustVendorDir();
         break;
     }
 }
\ No newline at end of file
@@ -1,38 +1,190 @@
 const { invoke } = window.__TAURI__.core;
 const { callFunction } = window.__TAURI__.python;
-const { Command } = window.__TAURI__.shell;
 const { exists, BaseDirectory } = window.__TAURI__.fs;
 
-async function rust_exec(codeString, inputs) {
-    return await invoke("rust_exec", { name: greetInputEl.value });
+// -------------------- Value ----------------------
+// 和 src-tauri/src/value.rs 同一套标签：JSON 表达不了的值包成单 key 对象
+const VALUE_TAGS = ['$int', '$float', '$bytes', '$error', '$map'];
+
+class NodeError {
+    constructor(message) {
+        this.message = message;
+    }
+    toString() {
+        return 'Error: ' + this.message;
+    }
 }
 
-async function python_exec(codeString, inputs) {
-    return await callFunction('python_exec', [codeString, inputs])   
+function bytesToBase64(bytes) {
+    let binary = '';
+    for (let i = 0; i < bytes.length; i++) {
+        binary += String.fromCharCode(bytes[i]);
+    }
+    return btoa(binary);
 }
 
-async function executeTerminalCommand(commandString, inputs) {
-    const ua = navigator.userAgent.toLowerCase();
-    let args;
+function base64ToBytes(text) {
+    const binary = atob(text);
+    const bytes = new Uint8Array(binary.length);
+    for (let i = 0; i < binary.length; i++) {
+        bytes[i] = binary.charCodeAt(i);
+    }
+    return bytes;
+}
+
+function encodeValue(value) {
+    if (value === undefined || value === null) {
+        return null;
+    }
+    if (typeof value === 'bigint') {
+        return Number.isSafeInteger(Number(value)) ? Number(value) : { '$int': value.toString() };
+    }
+    if (typeof value === 'number' && !Number.isFinite(value)) {
+        return { '$float': Number.isNaN(value) ? 'NaN' : (value > 0 ? 'inf' : '-inf') };
+    }
+    if (value instanceof Uint8Array || value instanceof ArrayBuffer) {
+        return { '$bytes': bytesToBase64(new Uint8Array(value)) };
+    }
+    if (value instanceof NodeError || value instanceof Error) {
+        return { '$error': value.message };
+    }
+    if (Array.isArray(value)) {
+        return value.map(encodeValue);
+    }
+    if (typeof value === 'object') {
+        const out = {};
+        for (const [key, item] of Object.entries(value)) {
+            out[key] = encodeValue(item);
+        }
+        const keys = Object.keys(out);
+        return keys.length === 1 && VALUE_TAGS.includes(keys[0]) ? { '$map': out } : out;
+    }
+    return value;
+}
 
-    if (ua.includes('win')) {
-        // 使用配置中允许的 “cmd” 或 “powershell”
-        if (inputs) {
-            // todo
+function decodeValue(value) {
+    if (Array.isArray(value)) {
+        return value.map(decodeValue);
+    }
+    if (value === null || typeof value !== 'object') {
+        return value;
+    }
+    const keys = Object.keys(value);
+    if (keys.length === 1 && VALUE_TAGS.includes(keys[0])) {
+        const inner = value[keys[0]];
+        switch (keys[0]) {
+            case '$int':
+                return BigInt(inner);
+            case '$float':
+                return inner === 'NaN' ? NaN : (inner === 'inf' ? Infinity : (inner === '-inf' ? -Infinity : Number(inner)));
+            case '$bytes':
+                return base64ToBytes(inner);
+            case '$error':
+                return new NodeError(inner);
+            case '$map':
+                value = inner;
+            break;
         }
-        window.shell = 'cmd';
-        args = ['/c', commandString]; // 或使用 powershell: ['-Command', commandString]
-    } else if (ua.includes('mac') || ua.includes('darwin')) {
-        window.shell = 'zsh';
-        args = ['-c', commandString];
-    } else if (ua.includes('linux')) {
-        window.shell = 'bash';
-        args = ['-c', commandString];
-    } else {
-        return 'unknown platform'
-    }
-    const cmd = Command.create(window.shell, args);
-    const output = await cmd.execute();
-    console.log('shell: ', output)
-    return output;
-}
\ No newline at end of file
+    }
+    const out = {};
+    for (const [key, item] of Object.entries(value)) {
+        out[key] = decodeValue(item);
+    }
+    return out;
+}
+
+// envelope 里只有 result 需要解码
+function decodeEnvelope(envelope) {
+    if (envelope && envelope.result !== undefined) {
+        envelope.result = decodeValue(envelope.result);
+    }
+    return envelope;
+}
+
+async function rust_exec(codeString, inputs, deps) {
+    return decodeEnvelope(await invoke("rust_exec", { code: codeString, deps: deps || [], inputs: encodeValue(inputs) }));
+}
+
+// crates for Rust nodes are resolved offline from this directory
+async function pickRustVendorDir() {
+    const vendorDir = await open({
+        directory: true,
+        multiple: false,
+        title: 'cargo vendor directory'
+    });
+    if (!vendorDir) {
+        return;
+    }
+    try {
+        await invoke("rust_settings_set", { value: { vendorDir } });
+        showHint('vendor directory\n' + vendorDir);
+    } catch (error) {
+        console.error('set vendor directory failed:', error);
+        showHint('!!!' + error + '!!!');
+    }
+}
+
+// interpreted in the backend, no rustc involved
+async function rust_eval(codeString, inputs) {
+    return decodeEnvelope(await invoke("rust_eval", { code: codeString, inputs: encodeValue(inputs) }));
+}
+
+// sandboxed wasi module, fuel / memory limits come from the node properties
+async function wasm_exec(codeString, inputs, properties) {
+    return decodeEnvelope(await invoke("wasm_exec", { code: codeString, inputs: encodeValue(inputs), properties }));
+}
+
+// runs the whole graph in the backend and writes the outputs back onto the canvas
+// step: pause before the first node (debug toolbar's Next)
+async function run_graph(step = false) {
+    // path: workflow/Call 的相对路径按当前文件所在的目录算
+    const report = await invoke("graph_run", { graph: window.graph.serialize(), step, path: targetPath || null });
+    for (const run of report.nodes) {
+        const node = window.graph.getNodeById(run.id);
+        if (!node) {
+            continue;
+        }
+        run.outputs.forEach((value, i) => node.setOutputData(i, decodeValue(value)));
+        node.diagnostics = run.envelope.diagnostics || [];
+        document.dispatchEvent(new CustomEvent('nodeDiagnostics', {
+            detail: node
+        }));
+    }
+    if (!report.ok) {
+        console.error(report.error);
+        showHint('!!!' + report.error + '!!!');
+    } else if (report.warning) {
+        console.warn(report.warning);
+        showHint(report.warning);
+    }
+    return report;
+}
+
+// upgrades older files and stamps extra.hpFormat; rejects files from a newer hp
+async function migrate_graph(graphData) {
+    return await invoke("migrate_graph", { graph: graphData });
+}
+
+// {script, unsupported: [{nodeId, title, reason}]}; target is 'bash' or 'python'
+async function compile_graph(graphData, target) {
+    return await invoke("compile_graph", { graph: graphData, target });
+}
+
+// {inputs, outputs}: names of the graph/input and graph/output nodes of a workflow file
+async function workflow_signature(path) {
+    return await invoke("workflow_signature", { path, caller: targetPath || null });
+}
+
+// structured findings: [{severity, code, message, nodeId?, linkId?, line?}]
+async function validate_graph(graphData) {
+    return await invoke("validate_graph", { graph: graphData });
+}
+
+async function python_exec(codeString, inputs) {
+    return decodeEnvelope(await callFunction('python_exec', [codeString, encodeValue(inputs)]));
+}
+
+// 平台对应的 shell 在后端选择，结果是 {code, signal?, stdout, stderr}
+async function executeTerminalCommand(commandString, inputs) {
+    return decodeEnvelope(await invoke("shell_exec", { command: commandString, inputs: encodeValue(inputs) }));
+}
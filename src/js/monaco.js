@@ -79,6 +79,30 @@ require(['vs/editor/editor.main'], function () {
 
     window.editor = newEditor("main-editor", defaultCode, "markdown");
 
+    // quick fixes from the suggestions attached to rustc diagnostics
+    monaco.languages.registerCodeActionProvider('rust', {
+        provideCodeActions(model, range) {
+            const node = findById(window.graph._nodes, editorCommManager.currentNodeId);
+            const actions = ((node && node.diagnostics) || [])
+                .filter(d => d.suggestion && monaco.Range.areIntersectingOrTouching(range, d.range))
+                .map(d => ({
+                    title: d.suggestion.message || `replace with ${d.suggestion.replacement}`,
+                    kind: 'quickfix',
+                    edit: {
+                        edits: [{
+                            resource: model.uri,
+                            versionId: model.getVersionId(),
+                            textEdit: {
+                                range: d.suggestion.range,
+                                text: d.suggestion.replacement
+                            }
+                        }]
+                    }
+                }));
+            return { actions, dispose() {} };
+        }
+    });
+
     
     // 全局函数，用于调整nav布局
     window.resizeMonacoEditor = function() {
@@ -110,6 +134,12 @@ class EditorCommunicationManager {
                 this.handleNewDataComing(event.detail);
             }
         });
+        // a node finished running, refresh markers if it is the one being edited
+        document.addEventListener('nodeDiagnostics', (event) => {
+            if (event.detail.id === this.currentNodeId && window.editContentType === 'code') {
+                this.updateMarkers(event.detail);
+            }
+        });
     }
     
     // call when node selected or change edit content(node-config or node's function)
@@ -130,6 +160,9 @@ class EditorCommunicationManager {
                     inputs: nodeData.inputs,
                     outputs: nodeData.outputs,
                 };
+                if (nodeData.properties.codeType === 'rust') {
+                    data.dependencies = nodeData.properties.deps || [];
+                }
                 data = jsonToSimple(data);
                 contentType = 'yaml';
             break;
@@ -140,6 +173,11 @@ class EditorCommunicationManager {
                     data = this.welcomeContent(nodeData)
                 }
                 contentType = nodeData.properties.codeType;
+                if (contentType === 'rustlite') {
+                    contentType = 'rust';
+                } else if (contentType === 'wasm') {
+                    contentType = 'plaintext';
+                }
             break;
         
             default:
@@ -151,6 +189,22 @@ class EditorCommunicationManager {
             data,
             contentType
         );
+        this.updateMarkers(window.editContentType === 'code' ? nodeData : null);
+    }
+
+    updateMarkers(nodeData) {
+        const severities = {
+            error: monaco.MarkerSeverity.Error,
+            warning: monaco.MarkerSeverity.Warning,
+            info: monaco.MarkerSeverity.Info
+        };
+        const diagnostics = (nodeData && nodeData.diagnostics) || [];
+        monaco.editor.setModelMarkers(window.editor.getModel(), 'rustc', diagnostics.map(d => ({
+            ...d.range,
+            severity: severities[d.severity] || monaco.MarkerSeverity.Error,
+            message: d.suggestion ? `${d.message}\nhelp: ${d.suggestion.message}` : d.message,
+            code: d.code || undefined
+        })));
     }
     
     updateMonacoEditor(data, contentType) {
@@ -206,7 +260,26 @@ class EditorCommunicationManager {
             case 'sh':
                 data = 'echo hello';
             break;
+            case 'rustlite':
+                if (nodeData.inputs) {
+                    inputsCode = nodeData.inputs.map((input, i) => `let ${input.name} = inputs["input_${i}"];`).join('\n');
+                }
+                data = [
+                    `${inputsCode}`,
+                    `// add, concat, vec_push, unwrap_or, println!...`,``,
+                    `${nodeData.inputs && nodeData.inputs.length ? nodeData.inputs[0].name : '()'}`
+                ].join('\n');
+            break;
             case 'rust':
+                // body of fn f(inputs: serde_json::Value) -> serde_json::Value
+                if (nodeData.inputs) {
+                    inputsCode = nodeData.inputs.map((input, i) => `let ${input.name} = &inputs["input_${i}"];`).join('\n');
+                }
+                data = [
+                    `${inputsCode}`,
+                    `// write your code here`,``,
+                    `json!(null)`
+                ].join('\n');
             break;
         
             default:
@@ -251,6 +324,9 @@ class EditorCommunicationManager {
             node.removeOutput(0);
         }
         node.title = newData.title;
+        if (node.properties.codeType === 'rust') {
+            node.properties.deps = newData.dependencies;
+        }
         // 添加新输入
         node.addInputs(newData.inputs.map(input => [
             input.name, 
@@ -356,13 +432,20 @@ function registerFunctionFromString(functionName, functionString) {
 // ]}`;
 
 function jsonToSimple(obj) {
-    const {title, inputs, outputs} = obj;
+    const {title, inputs, outputs, dependencies} = obj;
     
-    return `title: ${title}
+    let text = `title: ${title}
 inputs:
 ${inputs.map(i => `  - ${i.name} (${i.type})`).join('\n')}
 outputs:
 ${outputs.map(o => `  - ${o.name} (${o.type})`).join('\n')}`;
+    // rust node only: crate = "version" lines written into Cargo.toml
+    if (dependencies) {
+        text += `
+dependencies:
+${dependencies.map(d => `  - ${d}`).join('\n')}`;
+    }
+    return text;
 }
 
 function simpleToJson(simpleText) {
@@ -370,7 +453,8 @@ function simpleToJson(simpleText) {
     const result = {
         title: '',
         inputs: [],
-        outputs: []
+        outputs: [],
+        dependencies: []
     };
     
     let currentSection = '';
@@ -382,6 +466,10 @@ function simpleToJson(simpleText) {
             currentSection = 'inputs';
         } else if (line === 'outputs:') {
             currentSection = 'outputs';
+        } else if (line === 'dependencies:') {
+            currentSection = 'dependencies';
+        } else if (line.startsWith('  - ') && currentSection === 'dependencies') {
+            result.dependencies.push(line.slice(4).trim());
         } else if (line.startsWith('  - ') && currentSection) {
             const match = line.match(/  - (.+) \((.+)\)/);
             if (match) {
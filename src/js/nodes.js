@@ -9,7 +9,8 @@ function initializeDefaultNodes() {
             this.properties = {
                 description: "javascript node",
                 fn: "",
-                codeType: 'javascript'
+                codeType: 'javascript',
+                timeout: 0 // 秒，0 不限时
             };
         }
     }
@@ -23,11 +24,82 @@ function initializeDefaultNodes() {
             this.properties = {
                 description: "python node",
                 fn: "",
-                codeType: 'python'
+                codeType: 'python',
+                timeout: 0 // 秒，0 不限时
+            };
+        }
+    }
+
+    class rustNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "Rust";
+            this.addInput("input", "*");
+            this.addOutput("output", "*");
+            this.properties = {
+                description: "rust node",
+                fn: "",
+                deps: [],
+                codeType: 'rust',
+                timeout: 0 // 秒，0 不限时
+            };
+        }
+    }
+
+    class rustLiteNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "RustLite";
+            this.addInput("input", "*");
+            this.addOutput("output", "*");
+            this.properties = {
+                description: "interpreted rust node",
+                fn: "",
+                codeType: 'rustlite',
+                timeout: 0 // 秒，0 不限时
             };
         }
     }
 
+    class wasmNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "WebAssembly";
+            this.addInput("input", "*");
+            this.addOutput("output", "*");
+            this.properties = {
+                description: "wasi module, inputs on stdin, result on stdout",
+                fn: "",
+                codeType: 'wasm',
+                fuel: 1000000000,
+                memoryLimitMb: 64,
+                timeout: 0
+            };
+            this.addWidget("button", "load .wasm", null, async () => {
+                const path = await open({
+                    multiple: false,
+                    directory: false,
+                    filters: [{ name: 'wasm', extensions: ['wasm', 'wat'] }]
+                });
+                if (!path) {
+                    return;
+                }
+                const bytes = await readFile(path);
+                if (path.endsWith('.wat')) {
+                    this.properties.fn = new TextDecoder().decode(bytes);
+                } else {
+                    // binary modules are stored as base64
+                    let binary = '';
+                    for (let i = 0; i < bytes.length; i += 0x8000) {
+                        binary += String.fromCharCode.apply(null, bytes.subarray(i, i + 0x8000));
+                    }
+                    this.properties.fn = btoa(binary);
+                }
+                showHint('loaded ' + path.split(/[\\/]/).pop());
+            });
+        }
+    }
+
     class shellNode extends runtimeNode {
         constructor() {
             super();
@@ -37,7 +109,8 @@ function initializeDefaultNodes() {
             this.properties = {
                 description: "shell node",
                 fn: "",
-                codeType: 'sh'
+                codeType: 'sh',
+                timeout: 0
             };
             this.addWidget("text", "command", this.properties.fn, () => {
                 // what i should write??
@@ -52,7 +125,8 @@ function initializeDefaultNodes() {
             this.properties = {
                 description: "one to one shell node",
                 fn: "",
-                codeType: window.shell,
+                codeType: 'one2one',
+                timeout: 0
             };
             this.addWidget("text", "command", this.properties.fn, () => {
                 // what i should write??
@@ -60,14 +134,23 @@ function initializeDefaultNodes() {
             this.addInput("inputCmd1", "string");
             this.addOutput("returnValue1", "string");
         }
+        // 和后端 shell_node::one_to_one 一样：每个输入是一条命令，结果给同下标的输出，
+        // 不是字符串的输入给 null；fn 非空时最后跑，结果给所有输出
         async onExecute() {
             if (this.inputs) {
                 for (let i = 0; i < this.inputs.length; i++) {
+                    const command = this.getInputData(i);
+                    if (typeof command !== 'string' || command === '') {
+                        if (i < this.outputs.length) {
+                            this.setOutputData(i, null);
+                        }
+                        continue;
+                    }
                     try {
-                        let result = await executeTerminalCommand(this.getInputData(i));
+                        let result = await executeTerminalCommand(command);
                         // test todo
                         if (i < this.outputs.length) {
-                            this.setOutputData(i, result);
+                            this.setOutputData(i, result.result);
                         }
                     } catch(err) {
                         console.error(this.id+"号节点执行错误:", err);
@@ -78,15 +161,284 @@ function initializeDefaultNodes() {
                 let result = await executeTerminalCommand(this.properties.fn);
                 
                 for (let i = 0; i < this.outputs.length; i++) {
-                    this.setOutputData(i, result);
+                    this.setOutputData(i, result.result);
                 }   
             }
         }
     }
+    // -------------------- Control Nodes ----------------------
+    // 只有后端执行器认得：没走的输出口是 inactive，下游输入全是 inactive 的节点跳过
+    // fn 可选，是一段 rustlite 表达式，比如 inputs.input_0.code == 0
+    class ifNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "If";
+            this.addInput("value", "*");
+            this.addOutput("then", "*");
+            this.addOutput("else", "*");
+            this.properties = {
+                description: "passes value to then when the condition (fn, or value itself) is truthy, else to else",
+                fn: "",
+                codeType: 'if'
+            };
+        }
+    }
+
+    class switchNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "Switch";
+            this.addInput("value", "*");
+            this.addOutput("default", "*");
+            this.properties = {
+                description: "passes value to the output named like the key (fn, or value itself), else to default",
+                fn: "",
+                codeType: 'switch',
+                cases: ""
+            };
+        }
+        // cases 逗号分隔，每个 case 一个同名输出口；default 一直保留
+        onPropertyChanged(name, value) {
+            if (name !== "cases") {
+                return;
+            }
+            const cases = String(value).split(",").map((c) => c.trim()).filter(Boolean);
+            for (let i = this.outputs.length - 1; i >= 0; i--) {
+                const output = this.outputs[i].name;
+                if (output !== "default" && !cases.includes(output)) {
+                    this.removeOutput(i);
+                }
+            }
+            cases.forEach((c) => {
+                if (this.findOutputSlot(c) === -1) {
+                    this.addOutput(c, "*");
+                }
+            });
+        }
+    }
+
+    // bodyType 是 subgraph 时跑的子图，先放好边界节点：input 和 index 进，output 出
+    function bodySubgraph(node, input, output) {
+        node.subgraph = stampFormat(new LGraph());
+        node.subgraph._subgraph_node = node;
+        [["graph/input", input, 0], ["graph/input", "index", 60], ["graph/output", output, 0]].forEach(([type, name, y]) => {
+            const boundary = LiteGraph.createNode(type);
+            boundary.pos = [type === "graph/input" ? 50 : 400, 50 + y];
+            node.subgraph.add(boundary);
+            boundary.setProperty("name", name);
+        });
+    }
+
+    // 列表里每个元素跑一遍循环体：bodyType 是 python / sh / ... 时跑 fn（input_0 元素，input_1 下标），
+    // 是 subgraph 时跑节点里的子图（graph/input item / index，graph/output result）
+    // concurrency 0 是 CPU 个数；ordered 关掉时 results 按完成的先后排，失败的元素不占位
+    class mapNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "Map";
+            this.addInput("items", "array");
+            this.addOutput("results", "array");
+            this.addOutput("errors", "array");
+            this.properties = {
+                description: "runs the body once per element; errors is [{index, error}]",
+                bodyType: 'python',
+                fn: "",
+                codeType: 'map',
+                concurrency: 0,
+                ordered: true,
+                timeout: 0
+            };
+            bodySubgraph(this, "item", "result");
+        }
+        onSerialize(o) {
+            o.subgraph = this.subgraph.serialize();
+        }
+    }
+
+    // 图里不许连环，要循环用这两个节点：state 进，每轮循环体拿上一轮的 state（input_0）和下标（input_1），
+    // 返回下一轮的 state；子图当循环体时是 graph/input state / index，graph/output state
+    // 轮数超过 maxIterations 算失败
+    class whileNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "While";
+            this.addInput("state", "*");
+            this.addOutput("state", "*");
+            this.addOutput("iterations", "number");
+            this.properties = {
+                description: "runs the body while condition (rustlite, or state itself) is truthy",
+                condition: "",
+                bodyType: 'python',
+                fn: "",
+                codeType: 'while',
+                maxIterations: 1000,
+                timeout: 0
+            };
+            bodySubgraph(this, "state", "state");
+        }
+        onSerialize(o) {
+            o.subgraph = this.subgraph.serialize();
+        }
+    }
+
+    class repeatNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "Repeat";
+            this.addInput("state", "*");
+            this.addOutput("state", "*");
+            this.addOutput("iterations", "number");
+            this.properties = {
+                description: "runs the body count times",
+                count: 1,
+                bodyType: 'python',
+                fn: "",
+                codeType: 'repeat',
+                maxIterations: 1000,
+                timeout: 0
+            };
+            bodySubgraph(this, "state", "state");
+        }
+        onSerialize(o) {
+            o.subgraph = this.subgraph.serialize();
+        }
+    }
+
+    // -------------------- Subgraph Nodes ----------------------
+    // 整张子图存在节点的 subgraph 字段里，标题栏右侧的按钮打开编辑
+    // 里面的 graph/input / graph/output 按 properties.name 对应外面节点的同名输入 / 输出口
+    class subgraphNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "Subgraph";
+            this.properties = {
+                description: "runs the graph inside; graph/input and graph/output nodes stand for its ports",
+                codeType: 'subgraph'
+            };
+            this.subgraph = stampFormat(new LGraph());
+            this.subgraph._subgraph_node = this;
+        }
+        // LGraphNode.configure 会调用 this.subgraph.configure，读的时候不用管
+        onSerialize(o) {
+            o.subgraph = this.subgraph.serialize();
+        }
+    }
+
+    class graphInputNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "Input";
+            this.addOutput("value", "*");
+            this.properties = {
+                description: "the subgraph node's input with the same name",
+                name: "",
+                type: "*",
+                codeType: 'input'
+            };
+        }
+        onPropertyChanged(name, value) {
+            if (name === "name") {
+                this.title = value || "Input";
+                this.outputs[0].name = value || "value";
+            } else if (name === "type") {
+                this.outputs[0].type = value || "*";
+            }
+        }
+    }
+
+    class graphOutputNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "Output";
+            this.addInput("value", "*");
+            this.properties = {
+                description: "the subgraph node's output with the same name",
+                name: "",
+                type: "*",
+                codeType: 'output'
+            };
+        }
+        onPropertyChanged(name, value) {
+            if (name === "name") {
+                this.title = value || "Output";
+                this.inputs[0].name = value || "value";
+            } else if (name === "type") {
+                this.inputs[0].type = value || "*";
+            }
+        }
+    }
+
+    // -------------------- Workflow Nodes ----------------------
+    // 调用另一个存好的工作流文件：它顶层的 graph/input 是参数、graph/output 是结果
+    // path 相对当前文件所在的目录；改了 path 或者点 ports 按被调用的文件重新生成输入 / 输出口
+    class callNode extends runtimeNode {
+        constructor() {
+            super();
+            this.title = "Call";
+            this.properties = {
+                description: "runs another workflow file; its graph/input and graph/output nodes are the ports",
+                path: "",
+                codeType: 'call',
+                timeout: 0
+            };
+            this.addWidget("button", "ports", null, () => {
+                this.syncPorts();
+            });
+        }
+        onPropertyChanged(name) {
+            if (name === "path") {
+                this.syncPorts();
+            }
+        }
+        async syncPorts() {
+            if (!this.properties.path) {
+                return;
+            }
+            try {
+                const signature = await workflow_signature(this.properties.path);
+                for (let i = this.inputs ? this.inputs.length - 1 : -1; i >= 0; i--) {
+                    if (!signature.inputs.includes(this.inputs[i].name)) {
+                        this.removeInput(i);
+                    }
+                }
+                signature.inputs.forEach((name) => {
+                    if (this.findInputSlot(name) === -1) {
+                        this.addInput(name, "*");
+                    }
+                });
+                for (let i = this.outputs ? this.outputs.length - 1 : -1; i >= 0; i--) {
+                    if (!signature.outputs.includes(this.outputs[i].name)) {
+                        this.removeOutput(i);
+                    }
+                }
+                signature.outputs.forEach((name) => {
+                    if (this.findOutputSlot(name) === -1) {
+                        this.addOutput(name, "*");
+                    }
+                });
+                this.setDirtyCanvas(true, true);
+            } catch (error) {
+                showHint('!!!' + error + '!!!');
+            }
+        }
+    }
+
     LiteGraph.registerNodeType("code/Python", pythonNode);
     LiteGraph.registerNodeType("code/JavaScript", jsNode);
+    LiteGraph.registerNodeType("code/Rust", rustNode);
+    LiteGraph.registerNodeType("code/RustLite", rustLiteNode);
+    LiteGraph.registerNodeType("code/WebAssembly", wasmNode);
     LiteGraph.registerNodeType("shell/Terminal", shellNode);
     LiteGraph.registerNodeType("shell/one2oneTerminal", one2oneShellNode);
+    LiteGraph.registerNodeType("control/If", ifNode);
+    LiteGraph.registerNodeType("control/Switch", switchNode);
+    LiteGraph.registerNodeType("control/Map", mapNode);
+    LiteGraph.registerNodeType("control/While", whileNode);
+    LiteGraph.registerNodeType("control/Repeat", repeatNode);
+    LiteGraph.registerNodeType("graph/subgraph", subgraphNode);
+    LiteGraph.registerNodeType("graph/input", graphInputNode);
+    LiteGraph.registerNodeType("graph/output", graphOutputNode);
+    LiteGraph.registerNodeType("workflow/Call", callNode);
     // -------------------- Pre-prepared Nodes ----------------------
     class startNode extends runtimeNode {
         constructor() {
@@ -0,0 +1,108 @@
+// -------------------- Collapse To Subgraph ----------------------
+// 把选中的节点收进一个 graph/subgraph 节点：选区内部的连线搬进子图，
+// 跨出选区的连线变成子图节点的输入 / 输出口，每个口在子图里有一个同名的 graph/input / graph/output
+function uniqueSlotName(slots, name) {
+    const taken = new Set((slots || []).map((slot) => slot.name));
+    let unique = name || "value";
+    for (let i = 2; taken.has(unique); i++) {
+        unique = `${name}_${i}`;
+    }
+    return unique;
+}
+
+function collapseToSubgraph(canvas) {
+    const graph = canvas.graph;
+    const selected = Object.values(canvas.selected_nodes || {});
+    if (selected.length === 0) {
+        return;
+    }
+    const ids = new Set(selected.map((node) => node.id));
+    const links = Object.values(graph.links).filter(Boolean);
+    const internal = links.filter((link) => ids.has(link.origin_id) && ids.has(link.target_id));
+    const incoming = links.filter((link) => !ids.has(link.origin_id) && ids.has(link.target_id));
+    const outgoing = links.filter((link) => ids.has(link.origin_id) && !ids.has(link.target_id));
+
+    const left = Math.min(...selected.map((node) => node.pos[0]));
+    const top = Math.min(...selected.map((node) => node.pos[1]));
+    const right = Math.max(...selected.map((node) => node.pos[0] + node.size[0]));
+
+    graph.beforeChange();
+    const composite = LiteGraph.createNode("graph/subgraph");
+    const inner = composite.subgraph;
+
+    // 节点原样搬进子图，id 不变，连线之后重新连
+    const clones = new Map();
+    selected.forEach((node) => {
+        const info = LiteGraph.cloneObject(node.serialize());
+        (info.inputs || []).forEach((input) => { input.link = null; });
+        (info.outputs || []).forEach((output) => { output.links = null; });
+        const clone = LiteGraph.createNode(info.type);
+        clone.configure(info);
+        inner.add(clone);
+        clones.set(node.id, clone);
+    });
+    internal.forEach((link) => {
+        clones.get(link.origin_id).connect(link.origin_slot, clones.get(link.target_id), link.target_slot);
+    });
+
+    // 同一个外部输出连进来几次，也只开一个输入口
+    const inputs = new Map();
+    incoming.forEach((link) => {
+        const key = `${link.origin_id}:${link.origin_slot}`;
+        const target = clones.get(link.target_id);
+        if (!inputs.has(key)) {
+            const name = uniqueSlotName(composite.inputs, target.inputs[link.target_slot].name);
+            composite.addInput(name, link.type);
+            const boundary = LiteGraph.createNode("graph/input");
+            boundary.pos = [left - 200, top + inputs.size * 60];
+            inner.add(boundary);
+            boundary.setProperty("name", name);
+            boundary.setProperty("type", link.type);
+            inputs.set(key, { slot: composite.inputs.length - 1, boundary, origin: link });
+        }
+        inputs.get(key).boundary.connect(0, target, link.target_slot);
+    });
+
+    // 一个内部输出连出去几次，也只开一个输出口
+    const outputs = new Map();
+    outgoing.forEach((link) => {
+        const key = `${link.origin_id}:${link.origin_slot}`;
+        if (!outputs.has(key)) {
+            const origin = clones.get(link.origin_id);
+            const name = uniqueSlotName(composite.outputs, origin.outputs[link.origin_slot].name);
+            composite.addOutput(name, link.type);
+            const boundary = LiteGraph.createNode("graph/output");
+            boundary.pos = [right + 60, top + outputs.size * 60];
+            inner.add(boundary);
+            boundary.setProperty("name", name);
+            boundary.setProperty("type", link.type);
+            origin.connect(link.origin_slot, boundary, 0);
+            outputs.set(key, { slot: composite.outputs.length - 1, targets: [] });
+        }
+        outputs.get(key).targets.push(link);
+    });
+
+    // 外面：删掉原来的节点（连线跟着删），换成子图节点再接回去
+    composite.pos = [left, top];
+    composite.size = composite.computeSize();
+    graph.add(composite);
+    selected.forEach((node) => graph.remove(node));
+    inputs.forEach(({ slot, origin }) => {
+        const node = graph.getNodeById(origin.origin_id);
+        if (node) {
+            node.connect(origin.origin_slot, composite, slot);
+        }
+    });
+    outputs.forEach(({ slot, targets }) => {
+        targets.forEach((link) => {
+            const node = graph.getNodeById(link.target_id);
+            if (node) {
+                composite.connect(slot, node, link.target_slot);
+            }
+        });
+    });
+    canvas.selectNodes([composite]);
+    graph.afterChange();
+    graph.setDirtyCanvas(true, true);
+    return composite;
+}
@@ -1,19 +1,26 @@
 const { open, save } = window.__TAURI__.dialog;
 const { readFile, writeFile } = window.__TAURI__.fs;
 let targetPath, sourcePath, rememberedFileName = undefined;
+// .yaml / .yml 用文本格式保存，方便 code review
+const isTextWorkflow = (path) => /\.ya?ml$/i.test(path);
+// 和 migrate.rs 的 FORMAT_VERSION 一致；新建的图（画布、内嵌子图）都打上，读盘时才需要迁移
+const HP_FORMAT = 2;
+function stampFormat(graph) {
+    graph.extra = graph.extra || {};
+    graph.extra.hpFormat = HP_FORMAT;
+    return graph;
+}
 // 导出图数据
 async function exportGraph() {
-    let graphData = window.graph.serialize();
-    const encoder = new TextEncoder();
-    graphData = JSON.stringify(graphData, null, 2);
-    graphData = encoder.encode(graphData);
-
     if (!targetPath) {
         targetPath = await save({
             title: 'save as',
             filters: [{
                 name: 'file',
                 extensions: ['json']
+            }, {
+                name: 'workflow text',
+                extensions: ['yaml', 'yml']
             }],
             defaultPath: `${rememberedFileName}`
         });
@@ -23,6 +30,13 @@ async function exportGraph() {
     }
     rememberedFileName = targetPath.split(/[\\/]/).pop()
     try {
+        let graphData = stampFormat(window.graph.serialize());
+        if (isTextWorkflow(targetPath)) {
+            graphData = await invoke("graph_to_text", { graph: graphData });
+        } else {
+            graphData = JSON.stringify(graphData, null, 2);
+        }
+        graphData = new TextEncoder().encode(graphData);
         await writeFile(targetPath, graphData);
         showHint('save successfully\n'+targetPath);
     } catch (error) {
@@ -41,28 +55,76 @@ async function importGraph() {
             title: 'open',
             filters: [{
                 name: rememberedFileName || 'file',
-                extensions: ['json']
+                extensions: ['json', 'yaml', 'yml']
             }]
         });
 
         if (!sourcePath) {
             return;
         }
-        targetPath = sourcePath;
-        rememberedFileName = sourcePath.split(/[\\/]/).pop()
         // 因为我们设置了 multiple: false，所以这里直接使用
         let graphData = await readFile(sourcePath);
         const decoder = new TextDecoder();
         graphData = decoder.decode(graphData);
-        graphData = JSON.parse(graphData);
+        if (isTextWorkflow(sourcePath)) {
+            graphData = await invoke("graph_from_text", { text: graphData });
+        } else {
+            graphData = JSON.parse(graphData);
+        }
+        // 老版本的文件先升级，新版本的文件在这里报错，不动当前的图
+        graphData = await migrate_graph(graphData);
+        targetPath = sourcePath;
+        rememberedFileName = sourcePath.split(/[\\/]/).pop()
         // 清空现有图
         window.graph.clear();
+        stampFormat(window.graph);
 
         window.graph.configure(graphData);
-        showHint('Import Successfully');
+
+        const findings = await validate_graph(graphData);
+        const errors = findings.filter(f => f.severity === 'error');
+        findings.forEach(f => console.warn('validate:', f));
+        if (errors.length) {
+            showHint('!!!' + errors.length + ' problem(s), first: ' + errors[0].message + '!!!');
+        } else {
+            showHint('Import Successfully');
+        }
 
     } catch (error) {
         console.error('导入失败:', error);
-        throw new Error(`导入失败: ${error.message}`);
+        showHint('!!!' + (error.message || error) + '!!!');
+        throw new Error(`导入失败: ${error.message || error}`);
     }
-}
\ No newline at end of file
+}
+// 导出成独立脚本：.sh 用 bash + jq，.py 用 python3
+async function exportScript() {
+    const scriptPath = await save({
+        title: 'export script',
+        filters: [{
+            name: 'bash',
+            extensions: ['sh']
+        }, {
+            name: 'python',
+            extensions: ['py']
+        }],
+        defaultPath: `${(rememberedFileName || 'flow').replace(/\.(json|ya?ml)$/i, '')}.sh`
+    });
+    if (!scriptPath) {
+        return;
+    }
+    try {
+        const target = /\.py$/i.test(scriptPath) ? 'python' : 'bash';
+        const compiled = await compile_graph(window.graph.serialize(), target);
+        await writeFile(scriptPath, new TextEncoder().encode(compiled.script));
+        compiled.unsupported.forEach(u => console.warn('unsupported:', u));
+        if (compiled.unsupported.length) {
+            showHint('!!!' + compiled.unsupported.length + ' node(s) cannot run in the script, first: '
+                + compiled.unsupported[0].title + ' (' + compiled.unsupported[0].reason + ')!!!');
+        } else {
+            showHint('export successfully\n' + scriptPath);
+        }
+    } catch (error) {
+        console.error('Export failed:', error);
+        showHint('!!!' + (error.message || error) + '!!!');
+    }
+}